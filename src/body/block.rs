@@ -9,6 +9,9 @@ use wasm_bindgen::prelude::*;
 
 use crate::world::World;
 use crate::body::BodyId;
+use crate::cells::{ Cells, CellFeature };
+use crate::error::PhysError;
+use crate::replay::Input;
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl World {
@@ -16,26 +19,105 @@ impl World {
    * Создает Block тело
    */
   pub fn block_create(&mut self, x: i32, y: i32) -> BodyId {
-    if x > self.cells.width || y > self.cells.height {
+    self.record(Input::BlockCreate { x, y });
+    self.cell_feature_create(x, y, CellFeature::Block)
+  }
+
+  /**
+   * Создает Block тело, возвращает ошибку для ячейки за границами мира
+   */
+  pub fn try_block_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_cell(x, y)?;
+    Ok(self.block_create(x, y))
+  }
+
+  /**
+   * Удаляет Block тело
+   */
+  pub fn block_remove(&mut self, id: BodyId) {
+    self.record(Input::BlockRemove { id });
+
+    if let Some((x, y)) = self.cell_feature_remove(id, Cells::is_block) {
+      self.wake_items_on_cell(x, y);
+    }
+  }
+
+  /**
+   * Удаляет Block тело, возвращает ошибку, если в ячейке нет блока
+   */
+  pub fn try_block_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_cell_feature(id, Cells::is_block)?;
+    self.block_remove(id);
+    Ok(())
+  }
+}
+
+impl World {
+  /**
+   * Установка содержимого ячейки (x, y), общая для Block тел
+   * и другого содержимого ячеек, возвращает идентификатор
+   * ячейки или 0 для ячейки за границами мира
+   *
+   * Если содержимое заменяет Block тело или одностороннюю
+   * платформу, лежащие на ячейке предметы начинают падать
+   */
+  pub(crate) fn cell_feature_create(
+    &mut self, x: i32, y: i32, feature: CellFeature
+  ) -> BodyId {
+    if !self.cells.contains(x, y) {
       return 0
     }
 
-    self.cells.set_block(x, y, true);
+    let was_solid = self.cells.is_block(x, y) || self.cells.is_one_way(x, y);
 
-    let id = y * self.cells.width + x + 1;
+    self.cells.set_feature(x, y, Some(feature));
 
-    id as u32
+    if was_solid {
+      self.wake_items_on_cell(x, y);
+    }
+
+    self.cells.get_cell_id(x, y)
   }
 
   /**
-   * Удаляет Block тело
+   * Удаление содержимого ячейки с идентификатором id, если
+   * проверка has_feature подтверждает наличие содержимого,
+   * возвращает координаты ячейки удаленного содержимого
    */
-  pub fn block_remove(&mut self, id: BodyId) {
-    let id = id as i32 - 1;
+  pub(crate) fn cell_feature_remove(
+    &mut self, id: BodyId, has_feature: fn(&Cells, i32, i32) -> bool
+  ) -> Option<(i32, i32)> {
+    let (x, y) = self.cells.get_cell_position(id)?;
+
+    if !has_feature(&self.cells, x, y) {
+      return None
+    }
 
-    let x = id % self.cells.width;
-    let y = id / self.cells.width;
+    self.cells.set_feature(x, y, None);
+
+    Some((x, y))
+  }
 
-    self.cells.set_block(x, y, false);
+  /**
+   * Проверка, что ячейка находится в границах мира
+   */
+  pub(crate) fn check_cell(&self, x: i32, y: i32) -> Result<(), PhysError> {
+    if !self.cells.contains(x, y) {
+      return Err(PhysError::CellOutOfWorld { x, y })
+    }
+
+    Ok(())
+  }
+
+  /**
+   * Проверка наличия содержимого в ячейке с идентификатором id
+   */
+  pub(crate) fn check_cell_feature(
+    &self, id: BodyId, has_feature: fn(&Cells, i32, i32) -> bool
+  ) -> Result<(), PhysError> {
+    match self.cells.get_cell_position(id) {
+      Some((x, y)) if has_feature(&self.cells, x, y) => Ok(()),
+      _ => Err(PhysError::UnknownBody(id))
+    }
   }
-}
\ No newline at end of file
+}
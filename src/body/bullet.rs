@@ -0,0 +1,226 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::{ BodyId, BodyClass, Body };
+use crate::engine::{
+  MICROS_IN_SECOND, Bounds, Rect, get_distance, get_gravity_offset
+};
+use crate::error::PhysError;
+use crate::hash::StateHasher;
+use crate::replay::Input;
+use crate::world::World;
+
+pub const BODY_BULLET_SIZE: i32 = 8;
+pub const BODY_BULLET_HALF_SIZE: i32 = BODY_BULLET_SIZE / 2;
+
+/**
+ * Ограничительный прямоугольник пули с точкой (x, y)
+ * в середине нижней стороны
+ */
+pub fn get_bullet_bounds(x: i32, y: i32) -> Bounds {
+  Bounds {
+    min_x: x - BODY_BULLET_HALF_SIZE,
+    max_x: x + BODY_BULLET_HALF_SIZE,
+    min_y: y - BODY_BULLET_SIZE,
+    max_y: y
+  }
+}
+
+/**
+ * Тело пули
+ *
+ * Движется с постоянной скоростью и, при необходимости,
+ * с ускорением падения. Позиция рассчитывается от точки выстрела
+ * по времени полета, поэтому ошибки округления не накапливаются
+ */
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodyBullet {
+  pub x: i32,
+  pub y: i32,
+  pub prev_x: i32,
+  pub prev_y: i32,
+  start_x: i32,
+  start_y: i32,
+  // Скорость (пунктов в секунду)
+  velocity_x: i32,
+  velocity_y: i32,
+  // Ускорение падения (пунктов в секунду за секунду)
+  gravity: i32,
+  // Время полета (в микросекундах)
+  timer: i32,
+  // Идентификатор тела, выпустившего пулю
+  pub owner: BodyId
+}
+
+impl BodyBullet {
+  pub fn new(
+    x: i32, y: i32, velocity_x: i32, velocity_y: i32, owner: BodyId
+  ) -> Self {
+    Self {
+      x,
+      y,
+      prev_x: x,
+      prev_y: y,
+      start_x: x,
+      start_y: y,
+      velocity_x,
+      velocity_y,
+      owner,
+      ..Default::default()
+    }
+  }
+
+  /**
+   * Добавление состояния пули в контрольную сумму
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.x);
+    hasher.write_i32(self.y);
+    hasher.write_i32(self.prev_x);
+    hasher.write_i32(self.prev_y);
+    hasher.write_i32(self.start_x);
+    hasher.write_i32(self.start_y);
+    hasher.write_i32(self.velocity_x);
+    hasher.write_i32(self.velocity_y);
+    hasher.write_i32(self.gravity);
+    hasher.write_i32(self.timer);
+    hasher.write_u32(self.owner);
+  }
+
+  /**
+   * Установка ускорения падения, траектория продолжается
+   * из текущей точки с текущей скоростью
+   */
+  pub fn set_gravity(&mut self, gravity: i32) {
+    let velocity_y = self.velocity_y
+      + (self.gravity as i64 * 2 * self.timer as i64 / MICROS_IN_SECOND) as i32;
+
+    self.start_x = self.x;
+    self.start_y = self.y;
+    self.velocity_y = velocity_y;
+    self.gravity = gravity;
+    self.timer = 0;
+  }
+}
+
+impl Body for BodyBullet {
+  fn update(&mut self, delta: i32, rect: &mut Rect) {
+    self.timer = self.timer.saturating_add(delta);
+
+    self.x = self.start_x + get_distance(self.velocity_x, self.timer);
+    self.y = self.start_y
+      + get_distance(self.velocity_y, self.timer)
+      + get_gravity_offset(self.gravity as i64, self.timer);
+
+    rect.is_updated = true;
+    self.update_rect(rect);
+  }
+
+  fn update_rect(&mut self, rect: &mut Rect) {
+    rect.bounds = get_bullet_bounds(self.x, self.y);
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает тело пули в точке (x, y) со скоростью (vx, vy)
+   * пунктов в секунду, owner - тело, выпустившее пулю (или 0)
+   *
+   * Пуля уничтожается при попадании в Block тело или
+   * в тело игрока, кроме владельца
+   */
+  pub fn bullet_create(
+    &mut self, x: i32, y: i32, vx: i32, vy: i32, owner: BodyId
+  ) -> BodyId {
+    self.record(Input::BulletCreate { x, y, vx, vy, owner });
+
+    let id = self.next_body_id();
+
+    self.add_rect(Rect::new(
+      id, BodyClass::Bullet, x, y, BODY_BULLET_HALF_SIZE, BODY_BULLET_SIZE
+    ));
+
+    self.bullets.insert(id, BodyBullet::new(x, y, vx, vy, owner));
+
+    self.ids.insert(id);
+
+    id
+  }
+
+  /**
+   * Создает тело пули, возвращает ошибку для пули за границами
+   * мира и несуществующего владельца
+   */
+  pub fn try_bullet_create(
+    &mut self, x: i32, y: i32, vx: i32, vy: i32, owner: BodyId
+  ) -> Result<BodyId, PhysError> {
+    if owner != 0 {
+      self.check_body(owner)?;
+    }
+
+    self.check_position(x, y, BODY_BULLET_HALF_SIZE, BODY_BULLET_SIZE)?;
+    Ok(self.bullet_create(x, y, vx, vy, owner))
+  }
+
+  /**
+   * Создает тело пули аналогично bullet_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  #[allow(clippy::too_many_arguments)]
+  pub fn bullet_create_with_data(
+    &mut self, x: i32, y: i32, vx: i32, vy: i32, owner: BodyId,
+    user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.bullet_create(x, y, vx, vy, owner);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  pub fn try_bullet_create_with_data(
+    &mut self, x: i32, y: i32, vx: i32, vy: i32, owner: BodyId,
+    user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_bullet_create(x, y, vx, vy, owner)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+
+  /**
+   * Установка ускорения падения пули
+   * (пунктов в секунду за секунду, 0 - без падения)
+   */
+  pub fn bullet_set_gravity(&mut self, id: BodyId, gravity: i32) {
+    self.record(Input::BulletSetGravity { id, gravity });
+
+    if let Some(bullet) = self.bullets.get_mut(&id) {
+      bullet.set_gravity(gravity);
+    }
+  }
+
+  pub fn try_bullet_set_gravity(
+    &mut self, id: BodyId, gravity: i32
+  ) -> Result<(), PhysError> {
+    if !self.bullets.contains_key(&id) {
+      return Err(PhysError::UnknownBody(id))
+    }
+
+    self.bullet_set_gravity(id, gravity);
+    Ok(())
+  }
+
+  /**
+   * Владелец пули, 0 для неизвестной пули
+   *
+   * Пуля, уничтоженная на шаге, доступна до начала следующего шага
+   */
+  pub fn bullet_owner(&self, id: BodyId) -> BodyId {
+    match self.bullets.get(&id) {
+      Some(bullet) => bullet.owner,
+      None => 0
+    }
+  }
+}
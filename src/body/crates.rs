@@ -0,0 +1,542 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::{ BodyId, BodyClass, Body, Bodies, player::BodyPlayer };
+use crate::cells::Cells;
+use crate::engine::{
+  BLOCK_SIZE, Bounds, ObstaclesContacts, Rect, Rects,
+  get_bounds_intersection, get_gravity_offset
+};
+use crate::error::PhysError;
+use crate::grid::Grid;
+use crate::hash::StateHasher;
+use crate::replay::Input;
+use crate::world::World;
+
+// Ускорение падения (пунктов в секунду за секунду)
+const BODY_CRATE_GRAVITY: i64 = 1000;
+// Максимальный размер ящика, тело должно находиться
+// не более чем в 4 регионах сетки
+pub const BODY_CRATE_MAX_SIZE: i32 = 1024;
+// Максимальное количество ящиков, которые толкают друг друга
+const BODY_CRATE_MAX_PUSH_CHAIN: u32 = 8;
+
+/**
+ * Тело ящика
+ *
+ * Падает под действием ускорения, останавливается на Block телах,
+ * односторонних платформах и других ящиках. Для игроков является
+ * препятствием, игрок, идущий в ящик сбоку, толкает его
+ */
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodyCrate {
+  pub x: i32,
+  pub y: i32,
+  pub prev_x: i32,
+  pub prev_y: i32,
+  half_width: i32,
+  height: i32,
+  last_ground_y: i32,
+  fall_timer: i32,
+  pub is_on_ground: bool
+}
+
+impl BodyCrate {
+  pub fn new(x: i32, y: i32, half_width: i32, height: i32) -> Self {
+    Self {
+      x,
+      y,
+      prev_x: x,
+      prev_y: y,
+      half_width,
+      height,
+      last_ground_y: y,
+      ..Default::default()
+    }
+  }
+
+  /**
+   * Добавление состояния ящика в контрольную сумму
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.x);
+    hasher.write_i32(self.y);
+    hasher.write_i32(self.prev_x);
+    hasher.write_i32(self.prev_y);
+    hasher.write_i32(self.half_width);
+    hasher.write_i32(self.height);
+    hasher.write_i32(self.last_ground_y);
+    hasher.write_i32(self.fall_timer);
+    hasher.write_bool(self.is_on_ground);
+  }
+
+  /**
+   * Ограничительный прямоугольник ящика в текущей позиции
+   */
+  pub fn bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.x - self.half_width,
+      max_x: self.x + self.half_width,
+      min_y: self.y - self.height,
+      max_y: self.y
+    }
+  }
+
+  /**
+   * Ограничительный прямоугольник ящика
+   * в позиции на предыдущем шаге
+   */
+  pub fn prev_bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.prev_x - self.half_width,
+      max_x: self.prev_x + self.half_width,
+      min_y: self.prev_y - self.height,
+      max_y: self.prev_y
+    }
+  }
+
+  /**
+   * Остановка падения на поверхности с координатой ground_y
+   */
+  pub fn land(&mut self, ground_y: i32) {
+    self.y = ground_y;
+    self.last_ground_y = ground_y;
+    self.fall_timer = 0;
+    self.is_on_ground = true;
+  }
+
+  /**
+   * Начало падения, например, после того как ящик
+   * столкнули с опоры
+   */
+  pub fn wake(&mut self) {
+    self.last_ground_y = self.y;
+    self.fall_timer = 0;
+    self.is_on_ground = false;
+  }
+}
+
+impl Body for BodyCrate {
+  fn update(&mut self, delta: i32, rect: &mut Rect) {
+    if self.is_on_ground {
+      return
+    }
+
+    self.fall_timer = self.fall_timer.saturating_add(delta);
+
+    self.y = self.last_ground_y
+      + get_gravity_offset(BODY_CRATE_GRAVITY, self.fall_timer);
+
+    rect.is_updated = true;
+    self.update_rect(rect);
+  }
+
+  fn update_rect(&mut self, rect: &mut Rect) {
+    rect.bounds = self.bounds();
+  }
+}
+
+/**
+ * Идентификаторы других ящиков в регионах сетки,
+ * которые пересекает ограничительный прямоугольник,
+ * отсортированные для детерминированного порядка обхода
+ */
+fn get_other_crates(
+  grid: &Grid, crates: &Bodies<BodyCrate>, id: BodyId, bounds: &Bounds
+) -> Vec<BodyId> {
+  let mut result: Vec<BodyId> = grid.get_bodies_by_bounds(bounds)
+    .into_iter()
+    .filter(|other_id| *other_id != id && crates.contains_key(other_id))
+    .collect();
+
+  result.sort_unstable();
+
+  result
+}
+
+/**
+ * Проверка, стоит ли ящик на Block теле,
+ * односторонней платформе или другом ящике
+ */
+fn has_support(
+  cells: &Cells, grid: &Grid, crates: &Bodies<BodyCrate>, id: BodyId
+) -> bool {
+  let crate_body = &crates[&id];
+  let bounds = crate_body.bounds();
+
+  if bounds.max_y % BLOCK_SIZE == 0 {
+    let y_cell = bounds.max_y >> 7;
+
+    let is_on_cell = (bounds.min_x >> 7..=(bounds.max_x - 1) >> 7).any(|x_cell| {
+      cells.is_block(x_cell, y_cell) || cells.is_one_way(x_cell, y_cell)
+    });
+
+    if is_on_cell {
+      return true
+    }
+  }
+
+  get_other_crates(grid, crates, id, &bounds).iter().any(|other_id| {
+    let other_bounds = crates[other_id].bounds();
+
+    other_bounds.min_y == bounds.max_y
+    && other_bounds.min_x < bounds.max_x
+    && other_bounds.max_x > bounds.min_x
+  })
+}
+
+/**
+ * Приземление падающих ящиков на Block тела, односторонние
+ * платформы и другие ящики, и запуск падения ящиков,
+ * потерявших опору
+ *
+ * Ящики обрабатываются снизу вверх, поэтому ящик, падающий
+ * на другой падающий ящик, останавливается на его новой позиции
+ */
+pub fn update_correct_crates(
+  cells: &Cells, grid: &Grid, rects: &mut Rects,
+  crates: &mut Bodies<BodyCrate>
+) {
+  let mut ids: Vec<BodyId> = crates.keys().copied().collect();
+  ids.sort_unstable_by_key(|id| (-crates[id].prev_y, *id));
+
+  for id in ids {
+    let crate_body = &crates[&id];
+
+    if crate_body.is_on_ground {
+      if !has_support(cells, grid, crates, id) {
+        crates.get_mut(&id).unwrap().wake();
+      }
+
+      continue
+    }
+
+    if crate_body.y == crate_body.prev_y {
+      continue
+    }
+
+    let bounds = crate_body.bounds();
+    let prev_y = crate_body.prev_y;
+
+    let min_x = bounds.min_x >> 7;
+    let max_x = (bounds.max_x - 1) >> 7;
+
+    // Первый ряд ячеек, верхняя сторона которых не выше
+    // нижней стороны ящика на предыдущем шаге
+    let min_y = (prev_y + BLOCK_SIZE - 1) >> 7;
+    let max_y = bounds.max_y >> 7;
+
+    let mut ground_y = (min_y..=max_y).find(|y_cell| {
+      (min_x..=max_x).any(|x_cell| {
+        cells.is_block(x_cell, *y_cell) || cells.is_one_way(x_cell, *y_cell)
+      })
+    }).map(|y_cell| y_cell * BLOCK_SIZE);
+
+    let swept_bounds = Bounds {
+      min_y: prev_y - crate_body.height,
+      ..bounds.clone()
+    };
+
+    for other_id in get_other_crates(grid, crates, id, &swept_bounds) {
+      let other_bounds = crates[&other_id].bounds();
+
+      if other_bounds.min_x >= bounds.max_x
+      || other_bounds.max_x <= bounds.min_x
+      || other_bounds.min_y < prev_y
+      || other_bounds.min_y > bounds.max_y {
+        continue
+      }
+
+      if ground_y.is_none_or(|ground_y| other_bounds.min_y < ground_y) {
+        ground_y = Some(other_bounds.min_y);
+      }
+    }
+
+    if let Some(ground_y) = ground_y {
+      let crate_body = crates.get_mut(&id).unwrap();
+      crate_body.land(ground_y);
+
+      if let Some(rect) = rects.get_mut(&id) {
+        crate_body.update_rect(rect);
+      }
+    }
+  }
+}
+
+/**
+ * Смещение ящика по горизонтали на offset пунктов, ящик
+ * останавливается у Block тел и толкает другие ящики на своем пути.
+ * Возвращает фактическое смещение
+ */
+fn push_crate(
+  cells: &Cells, grid: &Grid, rects: &mut Rects,
+  crates: &mut Bodies<BodyCrate>, id: BodyId, offset: i32, depth: u32
+) -> i32 {
+  let bounds = crates[&id].bounds();
+  let direction = offset.signum();
+
+  let min_y = bounds.min_y >> 7;
+  let max_y = (bounds.max_y - 1) >> 7;
+
+  let is_blocked = |x_cell: i32| (min_y..=max_y).any(|y_cell| {
+    cells.is_block(x_cell, y_cell)
+  });
+
+  // Ограничение смещения первым столбцом Block тел на пути
+  let mut offset = offset;
+
+  if direction > 0 {
+    let first = bounds.max_x >> 7;
+    let last = (bounds.max_x + offset - 1) >> 7;
+
+    if let Some(x_cell) = (first..=last).find(|x_cell| is_blocked(*x_cell)) {
+      offset = (x_cell * BLOCK_SIZE - bounds.max_x).max(0);
+    }
+  } else {
+    let first = (bounds.min_x - 1) >> 7;
+    let last = (bounds.min_x + offset) >> 7;
+
+    if let Some(x_cell) = (last..=first).rev().find(|x_cell| is_blocked(*x_cell)) {
+      offset = ((x_cell + 1) * BLOCK_SIZE - bounds.min_x).min(0);
+    }
+  }
+
+  // Ящики на пути толкаются на оставшееся до них расстояние
+  let swept_bounds = Bounds {
+    min_x: bounds.min_x + offset.min(0),
+    max_x: bounds.max_x + offset.max(0),
+    ..bounds.clone()
+  };
+
+  for other_id in get_other_crates(grid, crates, id, &swept_bounds) {
+    let other_bounds = crates[&other_id].bounds();
+
+    if other_bounds.min_y >= bounds.max_y || other_bounds.max_y <= bounds.min_y {
+      continue
+    }
+
+    let gap = if direction > 0 {
+      other_bounds.min_x - bounds.max_x
+    } else {
+      bounds.min_x - other_bounds.max_x
+    };
+
+    if gap < 0 || gap >= offset.abs() {
+      continue
+    }
+
+    let moved = if depth < BODY_CRATE_MAX_PUSH_CHAIN {
+      push_crate(
+        cells, grid, rects, crates, other_id,
+        offset - gap * direction, depth + 1
+      )
+    } else {
+      0
+    };
+
+    offset = (gap * direction) + moved;
+  }
+
+  if offset == 0 {
+    return 0
+  }
+
+  let crate_body = crates.get_mut(&id).unwrap();
+  crate_body.x += offset;
+
+  if let Some(rect) = rects.get_mut(&id) {
+    rect.is_updated = true;
+    crate_body.update_rect(rect);
+  }
+
+  offset
+}
+
+/**
+ * Толкание ящиков игроками, которые вошли в них сбоку
+ * при движении по горизонтали
+ *
+ * Ящик смещается на глубину пересечения (насколько позволяют
+ * препятствия), оставшееся пересечение устраняется
+ * коррекцией позиции игрока
+ */
+pub fn update_push_crates(
+  cells: &Cells, grid: &Grid, rects: &mut Rects,
+  crates: &mut Bodies<BodyCrate>, players: &Bodies<BodyPlayer>,
+  obstacles_contacts: &ObstaclesContacts
+) {
+  let mut ids: Vec<&BodyId> = obstacles_contacts.keys().collect();
+  ids.sort_unstable();
+
+  for player_id in ids {
+    let (player_body, player_bounds) = match (
+      players.get(player_id), rects.get(player_id)
+    ) {
+      (Some(player_body), Some(rect)) => (player_body, rect.bounds.clone()),
+      _ => continue
+    };
+
+    if player_body.force_x == 0 {
+      continue
+    }
+
+    for crate_id in &obstacles_contacts[player_id] {
+      let crate_body = match crates.get(crate_id) {
+        Some(crate_body) => crate_body,
+        None => continue
+      };
+
+      let intersection = get_bounds_intersection(
+        &player_bounds, &crate_body.bounds()
+      );
+
+      if intersection.x <= 0 || intersection.y <= 0 {
+        continue
+      }
+
+      // Толкается только ящик, в который игрок вошел сбоку
+      // в направлении своего движения
+      let direction = if player_body.x < crate_body.x { 1 } else { -1 };
+
+      if direction != player_body.force_x.signum() {
+        continue
+      }
+
+      let prev_intersection = get_bounds_intersection(
+        &player_body.prev_bounds(), &crate_body.prev_bounds()
+      );
+
+      if prev_intersection.x > 0 || prev_intersection.y <= 0 {
+        continue
+      }
+
+      // Ящик сдвигается на глубину входа игрока, а не на ширину
+      // пересечения, которая не больше ширины ящика: узкий ящик
+      // иначе сдвигался бы меньше, чем прошел игрок, и игрок
+      // проходил бы сквозь него
+      let crate_bounds = crate_body.bounds();
+      let offset = if direction > 0 {
+        player_bounds.max_x - crate_bounds.min_x
+      } else {
+        player_bounds.min_x - crate_bounds.max_x
+      };
+
+      push_crate(cells, grid, rects, crates, *crate_id, offset, 0);
+    }
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает тело ящика шириной width и высотой height,
+   * (x, y) - середина нижней стороны ящика
+   *
+   * Ширина ограничивается диапазоном от 2 до BODY_CRATE_MAX_SIZE
+   * (у тела шириной 1 половина ширины была бы нулевой),
+   * высота - от 1 до BODY_CRATE_MAX_SIZE
+   */
+  pub fn crate_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> BodyId {
+    self.record(Input::CrateCreate { x, y, width, height });
+
+    let width = width.clamp(2, BODY_CRATE_MAX_SIZE);
+    let height = height.clamp(1, BODY_CRATE_MAX_SIZE);
+
+    let id = self.next_body_id();
+
+    self.add_rect(Rect::new(
+      id, BodyClass::Crate, x, y, width / 2, height
+    ));
+
+    self.crates.insert(id, BodyCrate::new(x, y, width / 2, height));
+
+    self.ids.insert(id);
+
+    id
+  }
+
+  /**
+   * Создает тело ящика, возвращает ошибку для ширины меньше 2,
+   * неположительной высоты, размеров больше BODY_CRATE_MAX_SIZE
+   * и ящика за границами мира
+   */
+  pub fn try_crate_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> Result<BodyId, PhysError> {
+    if width < 2 || height <= 0
+    || width > BODY_CRATE_MAX_SIZE || height > BODY_CRATE_MAX_SIZE {
+      return Err(PhysError::InvalidSize { width, height })
+    }
+
+    self.check_position(x, y, width / 2, height)?;
+    Ok(self.crate_create(x, y, width, height))
+  }
+
+  /**
+   * Создает тело ящика аналогично crate_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  pub fn crate_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.crate_create(x, y, width, height);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  pub fn try_crate_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_crate_create(x, y, width, height)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::engine::Direction;
+  use crate::world::World;
+
+  fn push_crate(width: i32) {
+    let mut world = World::new(60, 60);
+
+    for x in 0..60 {
+      world.block_create(x, 30);
+    }
+
+    let player_id = world.player_create(960, 3840);
+    let crate_id = world.crate_create(1500, 3840, width, 100);
+    let crate_min_x = world.rects[&crate_id].bounds.min_x;
+
+    world.player_run(player_id, Direction::Right);
+
+    for _ in 0..120 {
+      world.step(1.0 / 60.0);
+    }
+
+    let player_bounds = &world.rects[&player_id].bounds;
+    let crate_bounds = &world.rects[&crate_id].bounds;
+
+    assert!(crate_bounds.min_x > crate_min_x);
+    assert_eq!(crate_bounds.min_x, player_bounds.max_x);
+    assert_eq!(crate_bounds.max_y, 3840);
+  }
+
+  #[test]
+  fn player_pushes_crate() {
+    push_crate(100);
+  }
+
+  #[test]
+  fn player_pushes_narrow_crate() {
+    push_crate(1);
+    push_crate(4);
+  }
+}
@@ -2,16 +2,26 @@
 use wasm_bindgen::prelude::*;
 
 use crate::body::{ BodyId, BodyClass, Body };
-use crate::engine::Rect;
+use crate::engine::{ BLOCK_SIZE, Rect, get_gravity_offset };
+use crate::error::PhysError;
+use crate::hash::StateHasher;
+use crate::replay::Input;
 use crate::world::World;
 
 const BODY_ITEM_WIDTH: i32 = 64;
 const BODY_ITEM_HALF_WIDTH: i32 = BODY_ITEM_WIDTH / 2;
 const BODY_ITEM_HEIGHT: i32 = 64;
+// Ускорение падения (пунктов в секунду за секунду)
+const BODY_ITEM_GRAVITY: i64 = 1000;
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BodyItem {
   pub x: i32,
   pub y: i32,
+  pub prev_y: i32,
+  last_ground_y: i32,
+  fall_timer: i32,
   pub move_dir_y: i8,
   pub is_on_ground: bool
 }
@@ -19,33 +29,85 @@ pub struct BodyItem {
 impl BodyItem {
   pub fn new(x: i32, y: i32) -> Self {
     Self {
-      x: x,
-      y: y,
+      x,
+      y,
+      prev_y: y,
+      last_ground_y: y,
+      fall_timer: 0,
       move_dir_y: 0,
       is_on_ground: false
     }
   }
+
+  /**
+   * Добавление состояния предмета в контрольную сумму
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.x);
+    hasher.write_i32(self.y);
+    hasher.write_i32(self.prev_y);
+    hasher.write_i32(self.last_ground_y);
+    hasher.write_i32(self.fall_timer);
+    hasher.write_i8(self.move_dir_y);
+    hasher.write_bool(self.is_on_ground);
+  }
+
+  /**
+   * Остановка падения на поверхности с координатой ground_y
+   */
+  pub fn land(&mut self, ground_y: i32) {
+    self.y = ground_y;
+    self.last_ground_y = ground_y;
+    self.fall_timer = 0;
+    self.move_dir_y = 0;
+    self.is_on_ground = true;
+  }
+
+  /**
+   * Начало падения, например, после удаления блока под предметом
+   */
+  pub fn wake(&mut self) {
+    self.last_ground_y = self.y;
+    self.fall_timer = 0;
+    self.is_on_ground = false;
+  }
 }
 
 impl Body for BodyItem {
-  fn update(&mut self, _delta: f32, _rect: &mut Rect) {
+  fn update(&mut self, delta: i32, rect: &mut Rect) {
+    if self.is_on_ground {
+      return
+    }
 
-  }
+    self.fall_timer = self.fall_timer.saturating_add(delta);
+
+    self.y = self.last_ground_y
+      + get_gravity_offset(BODY_ITEM_GRAVITY, self.fall_timer);
+
+    self.move_dir_y = 1;
 
-  fn update_rect(&mut self, _rect: &mut Rect) {
+    rect.is_updated = true;
+    self.update_rect(rect);
+  }
 
+  fn update_rect(&mut self, rect: &mut Rect) {
+    rect.bounds.min_x = self.x - BODY_ITEM_HALF_WIDTH;
+    rect.bounds.max_x = self.x + BODY_ITEM_HALF_WIDTH;
+    rect.bounds.min_y = self.y - BODY_ITEM_HEIGHT;
+    rect.bounds.max_y = self.y;
   }
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl World {
   pub fn item_create(&mut self, x: i32, y: i32) -> BodyId {
+    self.record(Input::ItemCreate { x, y });
+
     let id = self.next_body_id();
 
-    self.rects.insert(id, Rect::new(
+    self.add_rect(Rect::new(
       id, BodyClass::Item, x, y, BODY_ITEM_HALF_WIDTH, BODY_ITEM_HEIGHT
     ));
-    self.grid.add(id, &mut self.rects);
 
     self.items.insert(id, BodyItem::new(x, y));
 
@@ -53,4 +115,54 @@ impl World {
 
     id
   }
-}
\ No newline at end of file
+
+  /**
+   * Создает тело предмета, возвращает ошибку, если тело
+   * выходит за границы мира
+   */
+  pub fn try_item_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_position(x, y, BODY_ITEM_HALF_WIDTH, BODY_ITEM_HEIGHT)?;
+    Ok(self.item_create(x, y))
+  }
+
+  /**
+   * Создает тело предмета аналогично item_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  pub fn item_create_with_data(
+    &mut self, x: i32, y: i32, user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.item_create(x, y);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  pub fn try_item_create_with_data(
+    &mut self, x: i32, y: i32, user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_item_create(x, y)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+}
+
+impl World {
+  /**
+   * Запуск падения предметов, лежащих на ячейке (x, y)
+   */
+  pub(crate) fn wake_items_on_cell(&mut self, x: i32, y: i32) {
+    let min_x = x * BLOCK_SIZE;
+    let max_x = min_x + BLOCK_SIZE;
+    let top = y * BLOCK_SIZE;
+
+    for item in self.items.values_mut() {
+      if item.is_on_ground
+      && item.y == top
+      && item.x + BODY_ITEM_HALF_WIDTH > min_x
+      && item.x - BODY_ITEM_HALF_WIDTH < max_x {
+        item.wake();
+      }
+    }
+  }
+}
@@ -0,0 +1,56 @@
+/**
+ * Лестница, как и Block тело, не имеет отдельного объекта,
+ * наличие лестницы определяется по занятости клетки в поле
+ * ladders в структуре ячеек Cells
+ *
+ * Лестница не мешает движению, игрок, пересекающий ее,
+ * может зацепиться за нее (player_climb) и двигаться
+ * по вертикали с постоянной скоростью без падения
+ */
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::world::World;
+use crate::body::BodyId;
+use crate::cells::{ Cells, CellFeature };
+use crate::error::PhysError;
+use crate::replay::Input;
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает лестницу в ячейке (x, y),
+   * идентификатор совпадает с идентификатором Block тела ячейки
+   */
+  pub fn ladder_create(&mut self, x: i32, y: i32) -> BodyId {
+    self.record(Input::LadderCreate { x, y });
+    self.cell_feature_create(x, y, CellFeature::Ladder)
+  }
+
+  /**
+   * Создает лестницу, возвращает ошибку для ячейки за границами мира
+   */
+  pub fn try_ladder_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_cell(x, y)?;
+    Ok(self.ladder_create(x, y))
+  }
+
+  /**
+   * Удаляет лестницу, игроки, которые больше не пересекают
+   * лестницу, начинают падение на следующем шаге
+   */
+  pub fn ladder_remove(&mut self, id: BodyId) {
+    self.record(Input::LadderRemove { id });
+    self.cell_feature_remove(id, Cells::is_ladder);
+  }
+
+  /**
+   * Удаляет лестницу, возвращает ошибку, если в ячейке нет лестницы
+   */
+  pub fn try_ladder_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_cell_feature(id, Cells::is_ladder)?;
+    self.ladder_remove(id);
+    Ok(())
+  }
+}
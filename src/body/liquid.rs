@@ -0,0 +1,57 @@
+/**
+ * Жидкость, как и Block тело, не имеет отдельного объекта,
+ * наличие жидкости определяется по занятости клетки в поле
+ * liquids в структуре ячеек Cells
+ *
+ * Жидкость не мешает движению, в ней игрок падает медленнее,
+ * двигается по горизонтали медленнее и может всплывать
+ * повторными прыжками (гребками)
+ */
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::world::World;
+use crate::body::BodyId;
+use crate::cells::{ Cells, CellFeature };
+use crate::error::PhysError;
+use crate::replay::Input;
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Заполняет ячейку (x, y) жидкостью,
+   * идентификатор совпадает с идентификатором Block тела ячейки
+   */
+  pub fn liquid_create(&mut self, x: i32, y: i32) -> BodyId {
+    self.record(Input::LiquidCreate { x, y });
+    self.cell_feature_create(x, y, CellFeature::Liquid)
+  }
+
+  /**
+   * Заполняет ячейку жидкостью, возвращает ошибку
+   * для ячейки за границами мира
+   */
+  pub fn try_liquid_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_cell(x, y)?;
+    Ok(self.liquid_create(x, y))
+  }
+
+  /**
+   * Удаляет жидкость из ячейки
+   */
+  pub fn liquid_remove(&mut self, id: BodyId) {
+    self.record(Input::LiquidRemove { id });
+    self.cell_feature_remove(id, Cells::is_liquid);
+  }
+
+  /**
+   * Удаляет жидкость из ячейки, возвращает ошибку,
+   * если в ячейке нет жидкости
+   */
+  pub fn try_liquid_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_cell_feature(id, Cells::is_liquid)?;
+    self.liquid_remove(id);
+    Ok(())
+  }
+}
@@ -1,6 +1,17 @@
 pub mod block;
+pub mod bullet;
+pub mod crates;
 pub mod item;
+pub mod ladder;
+pub mod liquid;
+pub mod one_way;
+pub mod platform;
 pub mod player;
+pub mod sensor;
+pub mod slope;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
 
 use std::collections::{ HashMap, HashSet };
 use crate::engine::Rect;
@@ -9,19 +20,42 @@ pub type BodyId = u32;
 
 pub type BodiesIds = HashSet<BodyId>;
 
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BodyClass {
   Fixed = 0,
   Sensor = 1,
   Player = 2,
   Ray = 3,
   Item = 4,
-  Bullet = 5
+  Bullet = 5,
+  Platform = 6,
+  Crate = 7
+}
+
+/**
+ * Пользовательские данные тела
+ *
+ * Не используются физическим движком, хранятся вместе с телом
+ * и передаются в событиях, чтобы игра могла связать тело
+ * со своими объектами без отдельной таблицы
+ */
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodyData {
+  // Произвольное значение игры
+  pub user_data: u64,
+  // Битовый набор меток
+  pub tags: u32
 }
 
 pub trait Body {
-  fn update(&mut self, delta: f32, rect: &mut Rect);
+  /**
+   * Обновление тела за время delta (в микросекундах)
+   */
+  fn update(&mut self, delta: i32, rect: &mut Rect);
   fn update_rect(&mut self, rect: &mut Rect);
 }
 
@@ -0,0 +1,60 @@
+/**
+ * Односторонняя платформа, как и Block тело, не имеет отдельного
+ * объекта, наличие платформы определяется по занятости клетки
+ * в поле one_way в структуре ячеек Cells
+ *
+ * Платформа останавливает тела игроков и предметов только сверху,
+ * игрок в прыжке проходит сквозь нее снизу, а стоящий на ней
+ * игрок может спрыгнуть вниз (player_drop)
+ */
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::world::World;
+use crate::body::BodyId;
+use crate::cells::{ Cells, CellFeature };
+use crate::error::PhysError;
+use crate::replay::Input;
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает одностороннюю платформу в ячейке (x, y),
+   * идентификатор совпадает с идентификатором Block тела ячейки
+   */
+  pub fn one_way_create(&mut self, x: i32, y: i32) -> BodyId {
+    self.record(Input::OneWayCreate { x, y });
+    self.cell_feature_create(x, y, CellFeature::OneWay)
+  }
+
+  /**
+   * Создает одностороннюю платформу, возвращает ошибку
+   * для ячейки за границами мира
+   */
+  pub fn try_one_way_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_cell(x, y)?;
+    Ok(self.one_way_create(x, y))
+  }
+
+  /**
+   * Удаляет одностороннюю платформу
+   */
+  pub fn one_way_remove(&mut self, id: BodyId) {
+    self.record(Input::OneWayRemove { id });
+
+    if let Some((x, y)) = self.cell_feature_remove(id, Cells::is_one_way) {
+      self.wake_items_on_cell(x, y);
+    }
+  }
+
+  /**
+   * Удаляет одностороннюю платформу, возвращает ошибку,
+   * если в ячейке нет платформы
+   */
+  pub fn try_one_way_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_cell_feature(id, Cells::is_one_way)?;
+    self.one_way_remove(id);
+    Ok(())
+  }
+}
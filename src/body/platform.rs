@@ -0,0 +1,443 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::{ BodyId, BodyClass, Body, Bodies, player::BodyPlayer };
+use crate::engine::{ MICROS_IN_SECOND, Bounds, Rect, Rects, get_distance };
+use crate::error::PhysError;
+use crate::hash::StateHasher;
+use crate::replay::Input;
+use crate::world::World;
+
+// Максимальный размер платформы, тело должно находиться
+// не более чем в 4 регионах сетки
+pub const BODY_PLATFORM_MAX_SIZE: i32 = 1024;
+
+/**
+ * Тело движущейся платформы
+ *
+ * Кинематическое тело: движется по заданному пути или с заданной
+ * скоростью и не реагирует на столкновения. Для игроков является
+ * опорой, как Block тела, и переносит стоящих на ней игроков.
+ *
+ * Если задан путь (точки пути), платформа движется от текущей точки
+ * к следующей точке пути со скоростью speed и по достижении последней
+ * точки возвращается к первой, иначе движется со скоростью
+ * (velocity_x, velocity_y). Позиция рассчитывается от начальной
+ * точки отрезка по времени движения, поэтому ошибки округления
+ * не накапливаются
+ */
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodyPlatform {
+  pub x: i32,
+  pub y: i32,
+  pub prev_x: i32,
+  pub prev_y: i32,
+  half_width: i32,
+  height: i32,
+  start_x: i32,
+  start_y: i32,
+  // Скорость (пунктов в секунду)
+  velocity_x: i32,
+  velocity_y: i32,
+  // Точки пути
+  waypoints: Vec<(i32, i32)>,
+  // Индекс точки пути, к которой движется платформа
+  waypoint: usize,
+  // Скорость движения по пути (пунктов в секунду)
+  speed: i32,
+  // Время движения от начальной точки (в микросекундах)
+  timer: i32
+}
+
+impl BodyPlatform {
+  pub fn new(x: i32, y: i32, half_width: i32, height: i32) -> Self {
+    Self {
+      x,
+      y,
+      prev_x: x,
+      prev_y: y,
+      half_width,
+      height,
+      start_x: x,
+      start_y: y,
+      ..Default::default()
+    }
+  }
+
+  /**
+   * Добавление состояния платформы в контрольную сумму
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.x);
+    hasher.write_i32(self.y);
+    hasher.write_i32(self.prev_x);
+    hasher.write_i32(self.prev_y);
+    hasher.write_i32(self.half_width);
+    hasher.write_i32(self.height);
+    hasher.write_i32(self.start_x);
+    hasher.write_i32(self.start_y);
+    hasher.write_i32(self.velocity_x);
+    hasher.write_i32(self.velocity_y);
+
+    for (x, y) in &self.waypoints {
+      hasher.write_i32(*x);
+      hasher.write_i32(*y);
+    }
+
+    hasher.write_u32(self.waypoint as u32);
+    hasher.write_i32(self.speed);
+    hasher.write_i32(self.timer);
+  }
+
+  /**
+   * Ограничительный прямоугольник платформы в текущей позиции
+   */
+  pub fn bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.x - self.half_width,
+      max_x: self.x + self.half_width,
+      min_y: self.y - self.height,
+      max_y: self.y
+    }
+  }
+
+  /**
+   * Ограничительный прямоугольник платформы
+   * в позиции на предыдущем шаге
+   */
+  pub fn prev_bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.prev_x - self.half_width,
+      max_x: self.prev_x + self.half_width,
+      min_y: self.prev_y - self.height,
+      max_y: self.prev_y
+    }
+  }
+
+  /**
+   * Начало нового отрезка движения из текущей точки
+   */
+  fn rebase(&mut self) {
+    self.start_x = self.x;
+    self.start_y = self.y;
+    self.timer = 0;
+  }
+
+  /**
+   * Установка скорости движения, путь платформы сбрасывается
+   */
+  pub fn set_velocity(&mut self, velocity_x: i32, velocity_y: i32) {
+    self.rebase();
+    self.waypoints.clear();
+    self.waypoint = 0;
+    self.velocity_x = velocity_x;
+    self.velocity_y = velocity_y;
+  }
+
+  /**
+   * Добавление точки в конец пути
+   */
+  pub fn add_waypoint(&mut self, x: i32, y: i32) {
+    if self.waypoints.is_empty() {
+      self.rebase();
+    }
+
+    self.waypoints.push((x, y));
+  }
+
+  /**
+   * Установка скорости движения по пути
+   */
+  pub fn set_speed(&mut self, speed: i32) {
+    self.rebase();
+    self.speed = speed.max(0);
+  }
+
+  /**
+   * Длительность движения от начальной точки до текущей
+   * точки пути (в микросекундах)
+   */
+  fn get_segment_duration(&self) -> i64 {
+    let (x, y) = self.waypoints[self.waypoint];
+    let distance_x = (x - self.start_x) as i64;
+    let distance_y = (y - self.start_y) as i64;
+    let distance = (distance_x * distance_x + distance_y * distance_y).isqrt();
+
+    distance * MICROS_IN_SECOND / self.speed as i64
+  }
+
+  fn update_path(&mut self) {
+    if self.speed == 0 {
+      return
+    }
+
+    let mut duration = self.get_segment_duration();
+
+    // Переход к следующим отрезкам пути, количество переходов
+    // ограничено, чтобы путь из совпадающих точек не зациклился
+    for _ in 0..=self.waypoints.len() {
+      if (self.timer as i64) < duration {
+        break
+      }
+
+      self.timer -= duration as i32;
+      (self.start_x, self.start_y) = self.waypoints[self.waypoint];
+      self.waypoint = (self.waypoint + 1) % self.waypoints.len();
+
+      duration = self.get_segment_duration();
+    }
+
+    if duration == 0 {
+      self.timer = 0;
+      self.x = self.start_x;
+      self.y = self.start_y;
+      return
+    }
+
+    let (x, y) = self.waypoints[self.waypoint];
+    let timer = self.timer as i64;
+
+    self.x = self.start_x + ((x - self.start_x) as i64 * timer / duration) as i32;
+    self.y = self.start_y + ((y - self.start_y) as i64 * timer / duration) as i32;
+  }
+
+  fn update_velocity(&mut self) {
+    // За каждую целую секунду платформа проходит ровно velocity
+    // пунктов, поэтому начальная точка переносится без потери
+    // точности, а время движения не переполняется
+    if self.timer as i64 >= MICROS_IN_SECOND {
+      let seconds = self.timer / MICROS_IN_SECOND as i32;
+
+      self.start_x += self.velocity_x * seconds;
+      self.start_y += self.velocity_y * seconds;
+      self.timer %= MICROS_IN_SECOND as i32;
+    }
+
+    self.x = self.start_x + get_distance(self.velocity_x, self.timer);
+    self.y = self.start_y + get_distance(self.velocity_y, self.timer);
+  }
+}
+
+impl Body for BodyPlatform {
+  fn update(&mut self, delta: i32, rect: &mut Rect) {
+    if self.waypoints.is_empty() {
+      if self.velocity_x == 0 && self.velocity_y == 0 {
+        return
+      }
+
+      self.timer = self.timer.saturating_add(delta);
+      self.update_velocity();
+    } else {
+      self.timer = self.timer.saturating_add(delta);
+      self.update_path();
+    }
+
+    if self.x == self.prev_x && self.y == self.prev_y {
+      return
+    }
+
+    rect.is_updated = true;
+    self.update_rect(rect);
+  }
+
+  fn update_rect(&mut self, rect: &mut Rect) {
+    rect.bounds = self.bounds();
+  }
+}
+
+/**
+ * Перенос игроков, стоящих на платформах, на смещение
+ * платформы за текущий шаг
+ *
+ * Вызывается после обновления позиций платформ и до обновления
+ * позиций игроков, поэтому игрок остается на платформе,
+ * даже если она опускается быстрее, чем игрок падает
+ */
+pub fn update_carry_players(
+  rects: &mut Rects, platforms: &Bodies<BodyPlatform>,
+  players: &mut Bodies<BodyPlayer>
+) {
+  for (id, player_body) in players.iter_mut() {
+    if player_body.ground_platform == 0 {
+      continue
+    }
+
+    let platform = match platforms.get(&player_body.ground_platform) {
+      Some(platform) => platform,
+      None => continue
+    };
+
+    let offset_x = platform.x - platform.prev_x;
+    let offset_y = platform.y - platform.prev_y;
+
+    if offset_x == 0 && offset_y == 0 {
+      continue
+    }
+
+    let rect = match rects.get_mut(id) {
+      Some(rect) => rect,
+      None => continue
+    };
+
+    player_body.x += offset_x;
+    player_body.y += offset_y;
+
+    rect.is_updated = true;
+    player_body.update_rect(rect);
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает тело платформы шириной width и высотой height,
+   * (x, y) - середина нижней стороны платформы
+   *
+   * Созданная платформа неподвижна, движение задается
+   * через platform_set_velocity или platform_add_waypoint.
+   * Ширина ограничивается диапазоном от 2 до BODY_PLATFORM_MAX_SIZE
+   * (у тела шириной 1 половина ширины была бы нулевой),
+   * высота - от 1 до BODY_PLATFORM_MAX_SIZE
+   */
+  pub fn platform_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> BodyId {
+    self.record(Input::PlatformCreate { x, y, width, height });
+
+    let width = width.clamp(2, BODY_PLATFORM_MAX_SIZE);
+    let height = height.clamp(1, BODY_PLATFORM_MAX_SIZE);
+
+    let id = self.next_body_id();
+
+    self.add_rect(Rect::new(
+      id, BodyClass::Platform, x, y, width / 2, height
+    ));
+
+    self.platforms.insert(id, BodyPlatform::new(x, y, width / 2, height));
+
+    self.ids.insert(id);
+
+    id
+  }
+
+  /**
+   * Создает тело платформы, возвращает ошибку для ширины меньше 2,
+   * неположительной высоты, размеров больше BODY_PLATFORM_MAX_SIZE
+   * и платформы за границами мира
+   */
+  pub fn try_platform_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> Result<BodyId, PhysError> {
+    if width < 2 || height <= 0
+    || width > BODY_PLATFORM_MAX_SIZE || height > BODY_PLATFORM_MAX_SIZE {
+      return Err(PhysError::InvalidSize { width, height })
+    }
+
+    self.check_position(x, y, width / 2, height)?;
+    Ok(self.platform_create(x, y, width, height))
+  }
+
+  /**
+   * Создает тело платформы аналогично platform_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  pub fn platform_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.platform_create(x, y, width, height);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  pub fn try_platform_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_platform_create(x, y, width, height)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+
+  /**
+   * Установка скорости платформы (пунктов в секунду),
+   * платформа перестает двигаться по пути
+   */
+  pub fn platform_set_velocity(&mut self, id: BodyId, vx: i32, vy: i32) {
+    self.record(Input::PlatformSetVelocity { id, vx, vy });
+
+    if let Some(platform) = self.platforms.get_mut(&id) {
+      platform.set_velocity(vx, vy);
+    }
+  }
+
+  pub fn try_platform_set_velocity(
+    &mut self, id: BodyId, vx: i32, vy: i32
+  ) -> Result<(), PhysError> {
+    self.check_platform(id)?;
+    self.platform_set_velocity(id, vx, vy);
+    Ok(())
+  }
+
+  /**
+   * Добавление точки (x, y) (середина нижней стороны платформы)
+   * в конец пути платформы
+   */
+  pub fn platform_add_waypoint(&mut self, id: BodyId, x: i32, y: i32) {
+    self.record(Input::PlatformAddWaypoint { id, x, y });
+
+    if let Some(platform) = self.platforms.get_mut(&id) {
+      platform.add_waypoint(x, y);
+    }
+  }
+
+  /**
+   * Добавляет точку пути платформы, возвращает ошибку, если
+   * платформа в этой точке выходит за границы мира
+   */
+  pub fn try_platform_add_waypoint(
+    &mut self, id: BodyId, x: i32, y: i32
+  ) -> Result<(), PhysError> {
+    let platform = self.platforms.get(&id)
+      .ok_or(PhysError::UnknownBody(id))?;
+
+    self.check_position(x, y, platform.half_width, platform.height)?;
+    self.platform_add_waypoint(id, x, y);
+    Ok(())
+  }
+
+  /**
+   * Установка скорости движения платформы по пути
+   * (пунктов в секунду, 0 - остановка)
+   */
+  pub fn platform_set_speed(&mut self, id: BodyId, speed: i32) {
+    self.record(Input::PlatformSetSpeed { id, speed });
+
+    if let Some(platform) = self.platforms.get_mut(&id) {
+      platform.set_speed(speed);
+    }
+  }
+
+  pub fn try_platform_set_speed(
+    &mut self, id: BodyId, speed: i32
+  ) -> Result<(), PhysError> {
+    self.check_platform(id)?;
+    self.platform_set_speed(id, speed);
+    Ok(())
+  }
+}
+
+impl World {
+  /**
+   * Проверка существования тела платформы
+   */
+  fn check_platform(&self, id: BodyId) -> Result<(), PhysError> {
+    if !self.platforms.contains_key(&id) {
+      return Err(PhysError::UnknownBody(id))
+    }
+
+    Ok(())
+  }
+}
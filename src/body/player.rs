@@ -2,59 +2,445 @@
 use wasm_bindgen::prelude::*;
 
 use std::cmp::Ordering;
-use crate::body::{ BodyId, BodyClass, Body };
-use crate::engine::{ Direction, Rect, Vector };
+use crate::body::{ BodyId, BodyClass, BodyData, Body, Bodies };
+use crate::cells::Cells;
+use crate::error::PhysError;
+use crate::hash::StateHasher;
+use crate::replay::Input;
+use crate::engine::{
+  MICROS_IN_SECOND, Bounds, Direction, Event, EventClass, Rect, Rects, Vector,
+  seconds_to_micros, get_distance, get_gravity_offset
+};
 use crate::world::World;
 
 pub const BODY_PLAYER_WIDTH: i32 = 64;
 pub const BODY_PLAYER_HALF_WIDTH: i32 = BODY_PLAYER_WIDTH / 2;
 pub const BODY_PLAYER_HEIGHT: i32 = 208;
-pub const BODY_PLAYER_GRAVITY: f32 = 0.001;
-// Максимальная высота прыжка
+// Максимальный размер тела игрока, тело должно находиться
+// не более чем в 4 регионах сетки
+pub const BODY_PLAYER_MAX_SIZE: i32 = 1024;
+// Скорость подъема, остающаяся при раннем отпускании прыжка
+// (в процентах от текущей скорости)
+pub const BODY_PLAYER_JUMP_RELEASE_PERCENT: i32 = 50;
+// Высота присевшего игрока (в процентах от высоты стоящего)
+pub const BODY_PLAYER_CROUCH_HEIGHT_PERCENT: i32 = 60;
+// Ускорение падения по умолчанию (пунктов в секунду за секунду)
+pub const BODY_PLAYER_GRAVITY: i32 = 1000;
+// Максимальная высота прыжка по умолчанию
 //pub const BODY_PLAYER_JUMP_DISTANCE: i32 = 320;
 pub const BODY_PLAYER_JUMP_DISTANCE: i32 = 160;
-// Коэффициент расчета расстояния в прыжке
-// = sqrt(BODY_PLAYER_JUMP_DISTANCE / BODY_PLAYER_GRAVITY)
-pub const BODY_PLAYER_JUMP_COEF: f32 = 400.0;
-pub const BODY_PLAYER_MOVE_SPEED: f32 = 750.0;
+// Скорость движения по умолчанию (пунктов в секунду)
+pub const BODY_PLAYER_MOVE_SPEED: i32 = 750;
+// Время после схода с опоры, в течение которого игрок еще может
+// прыгнуть, по умолчанию (в микросекундах)
+pub const BODY_PLAYER_COYOTE_TIME: i32 = 100_000;
+// Время, в течение которого нажатие прыжка в воздухе запоминается
+// и выполняется при приземлении, по умолчанию (в микросекундах)
+pub const BODY_PLAYER_JUMP_BUFFER: i32 = 100_000;
+// Скорость движения по лестнице (пунктов в секунду)
+pub const BODY_PLAYER_CLIMB_SPEED: i32 = 400;
+// Максимальное расстояние до наклонной поверхности снизу, на котором
+// стоящий игрок прижимается к ней при спуске, а не начинает падение
+pub const BODY_PLAYER_SNAP_DISTANCE: i32 = 32;
+// Ускорение падения в жидкости (пунктов в секунду за секунду)
+pub const BODY_PLAYER_SWIM_GRAVITY: i64 = 384;
+// Высота подъема за один гребок в жидкости
+pub const BODY_PLAYER_SWIM_DISTANCE: i32 = 96;
+// Коэффициент расчета расстояния гребка (в микросекундах)
+// = sqrt(BODY_PLAYER_SWIM_DISTANCE / BODY_PLAYER_SWIM_GRAVITY)
+pub const BODY_PLAYER_SWIM_COEF: i32 = 500_000;
+// Скорость горизонтального движения в жидкости
+// (в процентах от обычной скорости)
+pub const BODY_PLAYER_SWIM_SPEED_PERCENT: i32 = 50;
 
-#[derive(Default, Debug)]
+/**
+ * Параметры движения игрока
+ *
+ * Зависимые значения (коэффициент прыжка) рассчитываются
+ * при создании, поэтому параметры изменяются только
+ * через конструктор
+ */
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerConfig {
+  // Ускорение падения (пунктов в секунду за секунду)
+  gravity: i32,
+  // Максимальная высота прыжка
+  jump_distance: i32,
+  // Скорость движения (пунктов в секунду)
+  move_speed: i32,
+  // Коэффициент расчета расстояния в прыжке (в микросекундах)
+  // = sqrt(jump_distance / gravity)
+  jump_coef: i32,
+  // Время после схода с опоры, в течение которого
+  // игрок еще может прыгнуть (в микросекундах)
+  coyote_time: i32,
+  // Время, в течение которого запоминается нажатие
+  // прыжка в воздухе (в микросекундах)
+  jump_buffer: i32,
+  // Количество дополнительных прыжков в воздухе
+  air_jumps: u32
+}
+
+impl Default for PlayerConfig {
+  fn default() -> Self {
+    Self::new(
+      BODY_PLAYER_GRAVITY, BODY_PLAYER_JUMP_DISTANCE, BODY_PLAYER_MOVE_SPEED
+    )
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl PlayerConfig {
+  #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+  pub fn new(gravity: i32, jump_distance: i32, move_speed: i32) -> Self {
+    let gravity = gravity.max(1);
+    let jump_distance = jump_distance.max(0);
+
+    // Время подъема до высшей точки прыжка: gravity * t^2 = jump_distance
+    let jump_coef = (
+      jump_distance as i64 * MICROS_IN_SECOND * MICROS_IN_SECOND / gravity as i64
+    ).isqrt();
+
+    Self {
+      gravity,
+      jump_distance,
+      move_speed: move_speed.max(0),
+      jump_coef: jump_coef.min(i32::MAX as i64) as i32,
+      coyote_time: BODY_PLAYER_COYOTE_TIME,
+      jump_buffer: BODY_PLAYER_JUMP_BUFFER,
+      air_jumps: 0
+    }
+  }
+
+  pub fn gravity(&self) -> i32 {
+    self.gravity
+  }
+
+  pub fn jump_distance(&self) -> i32 {
+    self.jump_distance
+  }
+
+  pub fn move_speed(&self) -> i32 {
+    self.move_speed
+  }
+
+  pub fn jump_coef(&self) -> i32 {
+    self.jump_coef
+  }
+
+  /**
+   * Установка времени после схода с опоры, в течение
+   * которого игрок еще может прыгнуть (в секундах),
+   * 0 отключает прыжок после схода с опоры
+   */
+  pub fn set_coyote_time(&mut self, coyote_time: f32) {
+    self.coyote_time = seconds_to_micros(coyote_time).max(0);
+  }
+
+  pub fn coyote_time(&self) -> f32 {
+    self.coyote_time as f32 / MICROS_IN_SECOND as f32
+  }
+
+  /**
+   * Установка времени, в течение которого нажатие прыжка в воздухе
+   * запоминается и выполняется при приземлении (в секундах),
+   * 0 отключает запоминание прыжка
+   */
+  pub fn set_jump_buffer(&mut self, jump_buffer: f32) {
+    self.jump_buffer = seconds_to_micros(jump_buffer).max(0);
+  }
+
+  pub fn jump_buffer(&self) -> f32 {
+    self.jump_buffer as f32 / MICROS_IN_SECOND as f32
+  }
+
+  /**
+   * Установка количества дополнительных прыжков в воздухе
+   * (1 - двойной прыжок), счетчик сбрасывается при приземлении
+   */
+  pub fn set_air_jumps(&mut self, air_jumps: u32) {
+    self.air_jumps = air_jumps;
+  }
+
+  pub fn air_jumps(&self) -> u32 {
+    self.air_jumps
+  }
+
+  /**
+   * Добавление параметров в контрольную сумму
+   */
+  pub(crate) fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.gravity);
+    hasher.write_i32(self.jump_distance);
+    hasher.write_i32(self.move_speed);
+    hasher.write_i32(self.coyote_time);
+    hasher.write_i32(self.jump_buffer);
+    hasher.write_u32(self.air_jumps);
+  }
+
+  /**
+   * Упаковка параметров для записи ввода, зависимые
+   * значения не записываются и рассчитываются при распаковке
+   */
+  pub(crate) fn encode(&self, result: &mut Vec<i32>) {
+    result.extend_from_slice(&[
+      self.gravity, self.jump_distance, self.move_speed,
+      self.coyote_time, self.jump_buffer, self.air_jumps as i32
+    ]);
+  }
+
+  pub(crate) fn decode(args: &[i32]) -> Self {
+    Self {
+      coyote_time: args[3].max(0),
+      jump_buffer: args[4].max(0),
+      air_jumps: args[5].max(0) as u32,
+      ..Self::new(args[0], args[1], args[2])
+    }
+  }
+}
+
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BodyPlayer {
   pub x: i32,
   pub y: i32,
   pub prev_x: i32,
   pub prev_y: i32,
-  pub force_x: f32,
-  last_ground_y: i32,
+  pub force_x: i32,
+  pub last_ground_y: i32,
   pub is_jump: bool,
-  jump_timer: f32,
+  jump_timer: i32,
+  // Подъем текущего прыжка уже прерван отпусканием прыжка
+  jump_released: bool,
   jump_x_decreased: bool,
   jump_x_setted: bool,
   pub is_fall: bool,
-  fall_timer: f32,
-  move_dir_y: i8,
-  is_on_ground: bool,
+  fall_timer: i32,
+  // Падение началось со схода с опоры, в начале такого
+  // падения игрок еще может прыгнуть
+  is_coyote: bool,
+  // Время с момента схода с опоры (в микросекундах)
+  coyote_timer: i32,
+  // Нажатие прыжка в воздухе, которое будет выполнено
+  // при приземлении, и время с момента нажатия
+  is_jump_buffered: bool,
+  jump_buffer_timer: i32,
+  // Запомненный прыжок отпущен до приземления
+  jump_buffer_released: bool,
+  // Количество прыжков в воздухе с момента приземления
+  air_jumps_used: u32,
+  pub is_climb: bool,
+  // Направление движения по лестнице: -1 - вверх, 1 - вниз, 0 - на месте
+  climb_dir: i8,
+  pub move_dir_y: i8,
+  pub is_on_ground: bool,
   move_state: Direction,
-  current_tick_corrected: bool
+  current_tick_corrected: bool,
+  // Идентификатор платформы, на которой стоит игрок (или 0)
+  pub ground_platform: BodyId,
+  // Игрок спрыгивает вниз сквозь одностороннюю платформу
+  pub drop_down: bool,
+  // Игрок находится в жидкости
+  pub is_swim: bool,
+  // Текущий прыжок - гребок в жидкости
+  swim_stroke: bool,
+  config: PlayerConfig,
+  // Параметры движения заданы игроку отдельно (player_set_config)
+  // и не изменяются общими параметрами мира
+  pub has_own_config: bool,
+  half_width: i32,
+  // Текущая высота тела (с учетом приседания)
+  height: i32,
+  // Высота стоящего игрока
+  stand_height: i32,
+  pub is_crouch: bool
 }
 
 impl BodyPlayer {
-  pub fn new(x: i32, y: i32) -> Self {
+  pub fn new(x: i32, y: i32, config: PlayerConfig) -> Self {
     Self {
-      x: x,
-      y: y,
+      x,
+      y,
       prev_x: x,
       prev_y: y,
       last_ground_y: y,
+      config,
+      half_width: BODY_PLAYER_HALF_WIDTH,
+      height: BODY_PLAYER_HEIGHT,
+      stand_height: BODY_PLAYER_HEIGHT,
       ..Default::default()
     }
   }
 
+  pub fn half_width(&self) -> i32 {
+    self.half_width
+  }
+
+  pub fn height(&self) -> i32 {
+    self.height
+  }
+
+  pub fn stand_height(&self) -> i32 {
+    self.stand_height
+  }
+
+  fn get_crouch_height(&self) -> i32 {
+    (self.stand_height * BODY_PLAYER_CROUCH_HEIGHT_PERCENT / 100).max(1)
+  }
+
+  /**
+   * Ограничительный прямоугольник игрока в текущей позиции
+   */
+  pub fn bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.x - self.half_width,
+      max_x: self.x + self.half_width,
+      min_y: self.y - self.height,
+      max_y: self.y
+    }
+  }
+
+  /**
+   * Ограничительный прямоугольник игрока в позиции на предыдущем
+   * шаге (с текущим размером тела)
+   */
+  pub fn prev_bounds(&self) -> Bounds {
+    Bounds {
+      min_x: self.prev_x - self.half_width,
+      max_x: self.prev_x + self.half_width,
+      min_y: self.prev_y - self.height,
+      max_y: self.prev_y
+    }
+  }
+
+  /**
+   * Установка размера тела стоящего игрока,
+   * высота присевшего игрока пересчитывается
+   */
+  pub fn set_size(&mut self, width: i32, height: i32) {
+    self.half_width = width / 2;
+    self.stand_height = height;
+    self.height = if self.is_crouch { self.get_crouch_height() } else { height };
+  }
+
+  /**
+   * Приседание (is_crouch = true) и вставание, высота тела
+   * уменьшается и увеличивается вверх от нижней стороны
+   *
+   * can_stand - есть ли место над игроком, чтобы встать
+   */
+  pub fn crouch(&mut self, is_crouch: bool, can_stand: bool) {
+    if self.is_crouch == is_crouch || (!is_crouch && !can_stand) {
+      return
+    }
+
+    self.is_crouch = is_crouch;
+    self.height = if is_crouch { self.get_crouch_height() } else { self.stand_height };
+  }
+
+  pub fn config(&self) -> &PlayerConfig {
+    &self.config
+  }
+
+  /**
+   * Установка параметров движения
+   *
+   * Текущая скорость пересчитывается пропорционально новой
+   * скорости движения. При изменении ускорения падения или высоты
+   * прыжка прыжок и падение продолжаются по новой параболе
+   * из текущей точки с текущей вертикальной скоростью (скорость
+   * подъема не больше начальной скорости нового прыжка)
+   */
+  pub fn set_config(&mut self, config: PlayerConfig) {
+    let prev_config = self.config;
+    self.config = config;
+
+    if prev_config.move_speed != 0 {
+      self.force_x = (
+        self.force_x as i64 * config.move_speed as i64
+          / prev_config.move_speed as i64
+      ) as i32;
+    } else {
+      let move_state = self.move_state;
+      self.move_state = Direction::None;
+      self.run(move_state);
+    }
+
+    if prev_config.gravity == config.gravity
+    && prev_config.jump_distance == config.jump_distance {
+      return
+    }
+
+    // Вертикальная скорость в прыжке пропорциональна
+    // gravity * (jump_coef - jump_timer), в падении - gravity * fall_timer
+    if self.is_jump && !self.swim_stroke {
+      let rise = prev_config.gravity as i64
+        * (prev_config.jump_coef - self.jump_timer) as i64;
+      let jump_timer = config.jump_coef as i64 - rise / config.gravity as i64;
+
+      self.jump_timer = jump_timer.clamp(0, i32::MAX as i64) as i32;
+      self.last_ground_y = self.y + config.jump_distance - get_gravity_offset(
+        config.gravity as i64, self.jump_timer - config.jump_coef
+      );
+    }
+
+    if self.is_fall && !self.is_swim {
+      let fall_timer = prev_config.gravity as i64 * self.fall_timer as i64
+        / config.gravity as i64;
+
+      self.fall_timer = fall_timer.min(i32::MAX as i64) as i32;
+      self.last_ground_y = self.y
+        - get_gravity_offset(config.gravity as i64, self.fall_timer);
+    }
+  }
+
+  /**
+   * Добавление состояния игрока в контрольную сумму
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    hasher.write_i32(self.x);
+    hasher.write_i32(self.y);
+    hasher.write_i32(self.prev_x);
+    hasher.write_i32(self.prev_y);
+    hasher.write_i32(self.force_x);
+    hasher.write_i32(self.last_ground_y);
+    hasher.write_bool(self.is_jump);
+    hasher.write_i32(self.jump_timer);
+    hasher.write_bool(self.jump_released);
+    hasher.write_bool(self.jump_x_decreased);
+    hasher.write_bool(self.jump_x_setted);
+    hasher.write_bool(self.is_fall);
+    hasher.write_i32(self.fall_timer);
+    hasher.write_bool(self.is_coyote);
+    hasher.write_i32(self.coyote_timer);
+    hasher.write_bool(self.is_jump_buffered);
+    hasher.write_i32(self.jump_buffer_timer);
+    hasher.write_bool(self.jump_buffer_released);
+    hasher.write_u32(self.air_jumps_used);
+    hasher.write_bool(self.is_climb);
+    hasher.write_i8(self.climb_dir);
+    hasher.write_i8(self.move_dir_y);
+    hasher.write_bool(self.is_on_ground);
+    hasher.write_u8(self.move_state as u8);
+    hasher.write_u32(self.ground_platform);
+    hasher.write_bool(self.drop_down);
+    hasher.write_bool(self.is_swim);
+    hasher.write_bool(self.swim_stroke);
+    self.config.hash_state(hasher);
+    hasher.write_bool(self.has_own_config);
+    hasher.write_i32(self.half_width);
+    hasher.write_i32(self.height);
+    hasher.write_i32(self.stand_height);
+    hasher.write_bool(self.is_crouch);
+  }
+
   pub fn update_correction(&mut self, correction: &Vector) {
     self.current_tick_corrected = true;
 
     if correction.x != 0 && !self.is_on_ground && !self.jump_x_decreased {
-      self.force_x /= 2.0;
+      self.force_x /= 2;
       self.jump_x_decreased = true;
     }
 
@@ -62,14 +448,18 @@ impl BodyPlayer {
       Ordering::Less => {
         self.is_on_ground = true;
         self.is_jump = false;
-        self.jump_timer = 0.0;
+        self.jump_timer = 0;
         self.jump_x_decreased = false;
         self.jump_x_setted = false;
         self.is_fall = false;
-        self.fall_timer = 0.0;
+        self.fall_timer = 0;
+        self.drop_down = false;
+        self.is_climb = false;
+        self.climb_dir = 0;
+        self.air_jumps_used = 0;
 
         match self.move_state {
-          Direction::None => self.force_x = 0.0,
+          Direction::None => self.force_x = 0,
           _ => {
             let move_state = self.move_state;
             self.move_state = Direction::None;
@@ -85,7 +475,7 @@ impl BodyPlayer {
       },
       Ordering::Greater => {
         self.is_jump = false;
-        self.jump_timer = 0.0;
+        self.jump_timer = 0;
       },
       Ordering::Equal => {
         self.is_on_ground = false;
@@ -94,28 +484,44 @@ impl BodyPlayer {
   }
 
   pub fn after_update(&mut self) {
+    let was_on_ground = self.is_on_ground;
+
     if !self.current_tick_corrected {
       self.is_on_ground = false;
     }
 
+    if self.is_on_ground && self.is_jump_buffered {
+      let is_released = self.jump_buffer_released;
+
+      self.jump();
+
+      if is_released {
+        self.jump_release();
+      }
+    }
+
+    if !self.is_on_ground {
+      self.ground_platform = 0;
+    }
+
     if !self.is_on_ground
     && !self.is_jump
-    && !self.is_fall {
+    && !self.is_fall
+    && !self.is_climb {
       self.is_fall = true;
-      self.fall_timer = 0.0;
+      self.fall_timer = 0;
       self.last_ground_y = self.y;
+      self.is_coyote = was_on_ground;
+      self.coyote_timer = 0;
 
-      self.jump_x_setted = match self.move_state {
-        Direction::None => false,
-        _ => true
-      };
+      self.jump_x_setted = self.move_state != Direction::None;
 
       let direction_num = match self.move_state {
         Direction::None => return,
-        Direction::Left => -1.0,
-        Direction::Right => 1.0,
+        Direction::Left => -1,
+        Direction::Right => 1,
       };
-      self.force_x = BODY_PLAYER_MOVE_SPEED * direction_num;
+      self.force_x = self.config.move_speed * direction_num;
     }
   }
 
@@ -126,69 +532,241 @@ impl BodyPlayer {
 
     self.move_state = direction;
 
+    if self.is_climb {
+      let direction_num = match direction {
+        Direction::None => 0,
+        Direction::Left => -1,
+        Direction::Right => 1,
+      };
+      self.force_x = self.config.move_speed * direction_num;
+
+      return
+    }
+
     if !self.is_on_ground {
       if !self.jump_x_setted {
         self.jump_x_setted = true;
         self.jump_x_decreased = true;
 
         let direction_num = match direction {
-          Direction::None => 0.0,
-          Direction::Left => -1.0,
-          Direction::Right => 1.0,
+          Direction::None => 0,
+          Direction::Left => -1,
+          Direction::Right => 1,
         };
-        self.force_x = BODY_PLAYER_MOVE_SPEED * direction_num / 2.0;
+        self.force_x = self.config.move_speed * direction_num / 2;
       }
 
       return
     }
 
     let direction_num = match direction {
-      Direction::None => 0.0,
-      Direction::Left => -1.0,
-      Direction::Right => 1.0,
+      Direction::None => 0,
+      Direction::Left => -1,
+      Direction::Right => 1,
     };
-    self.force_x = BODY_PLAYER_MOVE_SPEED * direction_num;
+    self.force_x = self.config.move_speed * direction_num;
   }
 
+  /**
+   * Прыжок с опоры, с лестницы, в жидкости или в течение
+   * coyote_time после схода с опоры, иначе прыжок в воздухе,
+   * если они остались (дуга прыжка начинается с текущей высоты).
+   * В остальных случаях нажатие запоминается на jump_buffer
+   * и прыжок выполняется при приземлении
+   */
   pub fn jump(&mut self) {
-    if !self.is_on_ground {
-      return
+    let is_coyote = self.is_fall
+      && self.is_coyote
+      && self.coyote_timer <= self.config.coyote_time;
+
+    let can_jump = self.is_on_ground || self.is_climb || self.is_swim || is_coyote;
+
+    if !can_jump {
+      if self.air_jumps_used < self.config.air_jumps {
+        self.air_jumps_used += 1;
+      } else {
+        if self.config.jump_buffer > 0 {
+          self.is_jump_buffered = true;
+          self.jump_buffer_timer = 0;
+          self.jump_buffer_released = false;
+        }
+
+        return
+      }
     }
 
+    self.is_coyote = false;
+    self.is_jump_buffered = false;
+    self.jump_buffer_released = false;
+
+    self.is_climb = false;
+    self.climb_dir = 0;
+
     self.is_jump = true;
-    self.jump_timer = 0.0;
+    self.jump_timer = 0;
+    self.jump_released = false;
+    self.swim_stroke = self.is_swim;
+
+    self.is_fall = false;
+    self.fall_timer = 0;
 
     self.last_ground_y = self.y;
 
     self.is_on_ground = false;
+    self.ground_platform = 0;
 
-    self.jump_x_setted = match self.move_state {
-      Direction::None => false,
-      _ => true
+    self.jump_x_setted = self.move_state != Direction::None;
+  }
+
+  /**
+   * Раннее отпускание прыжка: скорость подъема уменьшается
+   * до BODY_PLAYER_JUMP_RELEASE_PERCENT процентов
+   *
+   * Прыжок продолжается по той же параболе, но с более поздней
+   * точки, из которой до высшей точки остается меньше времени,
+   * начало параболы смещается так, чтобы позиция не изменилась
+   */
+  pub fn jump_release(&mut self) {
+    if self.is_jump_buffered {
+      self.jump_buffer_released = true;
+      return
     }
+
+    if !self.is_jump || self.swim_stroke || self.jump_released {
+      return
+    }
+
+    let remaining = self.config.jump_coef - self.jump_timer;
+
+    if remaining <= 0 {
+      return
+    }
+
+    let remaining = (
+      remaining as i64 * BODY_PLAYER_JUMP_RELEASE_PERCENT as i64 / 100
+    ) as i32;
+
+    self.jump_released = true;
+    self.jump_timer = self.config.jump_coef - remaining;
+    self.last_ground_y = self.y + self.config.jump_distance
+      - get_gravity_offset(self.config.gravity as i64, remaining);
+  }
+
+  /**
+   * Спрыгивание вниз сквозь одностороннюю платформу, на которой
+   * стоит игрок, игрок начинает падение на следующем шаге
+   */
+  pub fn drop(&mut self) {
+    if !self.is_on_ground {
+      return
+    }
+
+    self.is_on_ground = false;
+    self.ground_platform = 0;
+    self.drop_down = true;
+  }
+
+  /**
+   * Движение по лестнице в направлении direction
+   * (-1 - вверх, 1 - вниз, 0 - остановка на лестнице)
+   *
+   * is_on_ladder - пересекает ли игрок лестницу, зацепиться
+   * можно только за лестницу, которую пересекает игрок
+   */
+  pub fn climb(&mut self, direction: i8, is_on_ladder: bool) {
+    if !self.is_climb {
+      if direction == 0 || !is_on_ladder {
+        return
+      }
+
+      self.is_climb = true;
+      self.is_on_ground = false;
+      self.ground_platform = 0;
+      self.is_jump = false;
+      self.jump_timer = 0;
+      self.is_fall = false;
+      self.fall_timer = 0;
+      self.jump_x_decreased = false;
+      self.jump_x_setted = false;
+
+      let direction_num = match self.move_state {
+        Direction::None => 0,
+        Direction::Left => -1,
+        Direction::Right => 1,
+      };
+      self.force_x = self.config.move_speed * direction_num;
+    }
+
+    self.climb_dir = direction;
+  }
+
+  /**
+   * Вход в жидкость и выход из нее
+   *
+   * Падение продолжается из текущей точки с новым ускорением,
+   * прыжок, начатый вне жидкости, при входе в нее переходит
+   * в падение, гребок продолжается до конца
+   */
+  pub fn set_swim(&mut self, is_swim: bool) {
+    if self.is_swim == is_swim {
+      return
+    }
+
+    self.is_swim = is_swim;
+
+    if is_swim && self.is_jump && !self.swim_stroke {
+      self.is_jump = false;
+      self.jump_timer = 0;
+      self.is_fall = true;
+    }
+
+    if self.is_fall {
+      self.fall_timer = 0;
+      self.last_ground_y = self.y;
+    }
+  }
+
+  /**
+   * Отпускание лестницы, которую игрок больше не пересекает,
+   * на следующем шаге игрок начинает падение
+   */
+  pub fn release_ladder(&mut self) {
+    self.is_climb = false;
+    self.climb_dir = 0;
   }
 }
 
 impl Body for BodyPlayer {
-  fn update(&mut self, delta: f32, rect: &mut Rect) {
-    //delta = delta / 4.0;
+  fn update(&mut self, delta: i32, rect: &mut Rect) {
     self.current_tick_corrected = false;
 
+    if self.is_jump_buffered {
+      self.jump_buffer_timer = self.jump_buffer_timer.saturating_add(delta);
+
+      if self.jump_buffer_timer > self.config.jump_buffer {
+        self.is_jump_buffered = false;
+      }
+    }
+
     if self.is_on_ground {
       rect.is_updated = true;
       self.y += 1;
       //self.is_on_ground = false;
     }
 
-    if self.force_x != 0.0 {
+    if self.force_x != 0 {
       rect.is_updated = true;
 
       // IMPORTANT: может быть проблема из-за округления
-      // при конвертации в i32 тип с отбрасыванием дробной части
+      // при целочисленном делении с отбрасыванием дробной части
       // (в виде уменьшения реальной скорости)
-      // При использовании округления .round(), будет аналогичный
-      // эффект с нестабильным уменьшением/увеличением скорости
-      self.x += (self.force_x * delta) as i32;
+      let force_x = if self.is_swim {
+        self.force_x * BODY_PLAYER_SWIM_SPEED_PERCENT / 100
+      } else {
+        self.force_x
+      };
+
+      self.x += get_distance(force_x, delta);
 
       //if self.is_on_ground {
       //  self.y += 1;
@@ -198,33 +776,52 @@ impl Body for BodyPlayer {
 
     self.move_dir_y = 0;
 
+    if self.is_climb && self.climb_dir != 0 {
+      self.y += get_distance(
+        BODY_PLAYER_CLIMB_SPEED * self.climb_dir as i32, delta
+      );
+
+      self.move_dir_y = self.climb_dir;
+
+      rect.is_updated = true;
+    }
+
     if self.is_jump {
-      self.jump_timer += delta * 1000.0;
+      self.jump_timer = self.jump_timer.saturating_add(delta);
+
+      let (gravity, coef, distance) = if self.swim_stroke {
+        (BODY_PLAYER_SWIM_GRAVITY, BODY_PLAYER_SWIM_COEF, BODY_PLAYER_SWIM_DISTANCE)
+      } else {
+        (
+          self.config.gravity as i64, self.config.jump_coef,
+          self.config.jump_distance
+        )
+      };
 
-      // IMPORTANT: может быть проблема из-за округления
-      // при конвертации в i32 тип с отбрасыванием дробной части
-      // (в виде уменьшения реальной скорости)
-      // При использовании округления .round(), будет аналогичный
-      // эффект с нестабильным уменьшением/увеличением скорости
       self.y = self.last_ground_y
-        + (BODY_PLAYER_GRAVITY * (self.jump_timer - BODY_PLAYER_JUMP_COEF).powf(2.0)) as i32
-        - BODY_PLAYER_JUMP_DISTANCE;
+        + get_gravity_offset(gravity, self.jump_timer - coef)
+        - distance;
 
-      self.move_dir_y = if self.jump_timer - BODY_PLAYER_JUMP_COEF > 0.0 { 1 } else { -1 };
+      self.move_dir_y = if self.jump_timer - coef > 0 { 1 } else { -1 };
 
       rect.is_updated = true;
     }
 
     if self.is_fall {
-      self.fall_timer += delta * 1000.0;
+      self.fall_timer = self.fall_timer.saturating_add(delta);
+
+      if self.is_coyote {
+        self.coyote_timer = self.coyote_timer.saturating_add(delta);
+      }
+
+      let gravity = if self.is_swim {
+        BODY_PLAYER_SWIM_GRAVITY
+      } else {
+        self.config.gravity as i64
+      };
 
-      // IMPORTANT: может быть проблема из-за округления
-      // при конвертации в i32 тип с отбрасыванием дробной части
-      // (в виде уменьшения реальной скорости)
-      // При использовании округления .round(), будет аналогичный
-      // эффект с нестабильным уменьшением/увеличением скорости
       self.y = self.last_ground_y
-        + (BODY_PLAYER_GRAVITY * self.fall_timer.powf(2.0)) as i32;
+        + get_gravity_offset(gravity, self.fall_timer);
 
       self.move_dir_y = 1;
 
@@ -237,41 +834,461 @@ impl Body for BodyPlayer {
   }
 
   fn update_rect(&mut self, rect: &mut Rect) {
-    rect.bounds.min_x = self.x - BODY_PLAYER_HALF_WIDTH;
-    rect.bounds.max_x = self.x + BODY_PLAYER_HALF_WIDTH;
-    rect.bounds.min_y = self.y - BODY_PLAYER_HEIGHT;
-    rect.bounds.max_y = self.y;
+    rect.bounds = self.bounds();
   }
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 impl World {
   pub fn player_create(&mut self, x: i32, y: i32) -> BodyId {
+    self.record(Input::PlayerCreate { x, y });
+
     let id = self.next_body_id();
 
-    self.rects.insert(id, Rect::new(
+    self.add_rect(Rect::new(
       id, BodyClass::Player, x, y, BODY_PLAYER_HALF_WIDTH, BODY_PLAYER_HEIGHT
     ));
-    self.grid.add(id, &mut self.rects);
 
-    self.players.insert(id, BodyPlayer::new(x, y));
+    self.players.insert(id, BodyPlayer::new(x, y, self.players_config));
 
     self.ids.insert(id);
 
     id
   }
 
-  pub fn player_run(&mut self, id: BodyId, direction: Direction) {
-    match self.players.get_mut(&id) {
-      Some(player) => player.run(direction),
-      None => ()
+  /**
+   * Создает тело игрока, возвращает ошибку, если тело
+   * выходит за границы мира
+   */
+  pub fn try_player_create(&mut self, x: i32, y: i32) -> Result<BodyId, PhysError> {
+    self.check_position(x, y, BODY_PLAYER_HALF_WIDTH, BODY_PLAYER_HEIGHT)?;
+    Ok(self.player_create(x, y))
+  }
+
+  /**
+   * Создает тело игрока аналогично player_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  pub fn player_create_with_data(
+    &mut self, x: i32, y: i32, user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.player_create(x, y);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  pub fn try_player_create_with_data(
+    &mut self, x: i32, y: i32, user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_player_create(x, y)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+
+  /**
+   * Создает тело игрока с параметрами движения config
+   * вместо общих параметров мира
+   */
+  pub fn player_create_with_config(
+    &mut self, x: i32, y: i32, config: &PlayerConfig
+  ) -> BodyId {
+    let id = self.player_create(x, y);
+    self.player_set_config(id, config);
+
+    id
+  }
+
+  pub fn try_player_create_with_config(
+    &mut self, x: i32, y: i32, config: &PlayerConfig
+  ) -> Result<BodyId, PhysError> {
+    self.check_position(x, y, BODY_PLAYER_HALF_WIDTH, BODY_PLAYER_HEIGHT)?;
+    Ok(self.player_create_with_config(x, y, config))
+  }
+
+  /**
+   * Установка параметров движения игрока
+   */
+  pub fn player_set_config(&mut self, id: BodyId, config: &PlayerConfig) {
+    self.record(Input::PlayerSetConfig { id, config: *config });
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.set_config(*config);
+      player.has_own_config = true;
+    }
+  }
+
+  pub fn try_player_set_config(
+    &mut self, id: BodyId, config: &PlayerConfig
+  ) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_set_config(id, config);
+    Ok(())
+  }
+
+  /**
+   * Параметры движения игрока, None для несуществующего игрока
+   */
+  pub fn player_config(&self, id: BodyId) -> Option<PlayerConfig> {
+    self.players.get(&id).map(|player| *player.config())
+  }
+
+  /**
+   * Установка общих параметров движения игроков: параметры
+   * применяются к существующим игрокам без собственных параметров
+   * (заданных через player_set_config) и к создаваемым через player_create
+   */
+  pub fn set_players_config(&mut self, config: &PlayerConfig) {
+    self.record(Input::SetPlayersConfig { config: *config });
+
+    self.players_config = *config;
+
+    for player in self.players.values_mut() {
+      if !player.has_own_config {
+        player.set_config(*config);
+      }
+    }
+  }
+
+  pub fn players_config(&self) -> PlayerConfig {
+    self.players_config
+  }
+
+  /**
+   * Установка размера тела игрока шириной width и высотой height
+   * (размер стоящего игрока), тело изменяется вверх и в стороны
+   * от середины нижней стороны
+   *
+   * Ширина ограничивается диапазоном от 2 до BODY_PLAYER_MAX_SIZE
+   * (у тела шириной 1 половина ширины была бы нулевой), высота -
+   * от 1 до BODY_PLAYER_MAX_SIZE. Размер не изменяется, если тело
+   * вошло бы в Block тело или наклонную ячейку
+   */
+  pub fn player_set_size(&mut self, id: BodyId, width: i32, height: i32) {
+    self.record(Input::PlayerSetSize { id, width, height });
+
+    let width = width.clamp(2, BODY_PLAYER_MAX_SIZE);
+    let height = height.clamp(1, BODY_PLAYER_MAX_SIZE);
+
+    let (player, rect) = match (
+      self.players.get_mut(&id), self.rects.get_mut(&id)
+    ) {
+      (Some(player), Some(rect)) => (player, rect),
+      _ => return
     };
+
+    if !can_resize(&self.cells, player, width, height) {
+      return
+    }
+
+    player.set_size(width, height);
+
+    rect.is_updated = true;
+    player.update_rect(rect);
+  }
+
+  /**
+   * Устанавливает размер тела игрока, возвращает ошибку для ширины
+   * меньше 2, неположительной высоты, размеров больше BODY_PLAYER_MAX_SIZE
+   * и размеров, при которых тело вошло бы в Block тело
+   * или наклонную ячейку
+   */
+  pub fn try_player_set_size(
+    &mut self, id: BodyId, width: i32, height: i32
+  ) -> Result<(), PhysError> {
+    self.check_player(id)?;
+
+    if width < 2 || height <= 0
+    || width > BODY_PLAYER_MAX_SIZE || height > BODY_PLAYER_MAX_SIZE
+    || !can_resize(&self.cells, &self.players[&id], width, height) {
+      return Err(PhysError::InvalidSize { width, height })
+    }
+
+    self.player_set_size(id, width, height);
+    Ok(())
+  }
+
+  /**
+   * Приседание игрока (is_crouch = true) и вставание
+   *
+   * Игрок не встает, если над ним Block тело или наклонная ячейка,
+   * в которые вошло бы тело стоящего игрока
+   */
+  pub fn player_crouch(&mut self, id: BodyId, is_crouch: bool) {
+    self.record(Input::PlayerCrouch { id, is_crouch });
+
+    let (player, rect) = match (
+      self.players.get_mut(&id), self.rects.get_mut(&id)
+    ) {
+      (Some(player), Some(rect)) => (player, rect),
+      _ => return
+    };
+
+    let can_stand = is_crouch || !has_overhead_block(&self.cells, player);
+
+    player.crouch(is_crouch, can_stand);
+
+    rect.is_updated = true;
+    player.update_rect(rect);
+  }
+
+  pub fn try_player_crouch(
+    &mut self, id: BodyId, is_crouch: bool
+  ) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_crouch(id, is_crouch);
+    Ok(())
+  }
+
+  pub fn player_run(&mut self, id: BodyId, direction: Direction) {
+    self.record(Input::PlayerRun { id, direction });
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.run(direction);
+    }
+  }
+
+  pub fn try_player_run(
+    &mut self, id: BodyId, direction: Direction
+  ) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_run(id, direction);
+    Ok(())
   }
 
   pub fn player_jump(&mut self, id: BodyId) {
-    match self.players.get_mut(&id) {
-      Some(player) => player.jump(),
-      None => ()
+    self.record(Input::PlayerJump { id });
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.jump();
+    }
+  }
+
+  pub fn try_player_jump(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_jump(id);
+    Ok(())
+  }
+
+  /**
+   * Отпускание прыжка, прерывает подъем, если прыжок
+   * отпущен до высшей точки
+   */
+  pub fn player_jump_release(&mut self, id: BodyId) {
+    self.record(Input::PlayerJumpRelease { id });
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.jump_release();
+    }
+  }
+
+  pub fn try_player_jump_release(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_jump_release(id);
+    Ok(())
+  }
+
+  /**
+   * Спрыгивание игрока вниз сквозь одностороннюю платформу
+   */
+  pub fn player_drop(&mut self, id: BodyId) {
+    self.record(Input::PlayerDrop { id });
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.drop();
+    }
+  }
+
+  pub fn try_player_drop(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_drop(id);
+    Ok(())
+  }
+
+  /**
+   * Движение игрока по лестнице: direction < 0 - вверх,
+   * direction > 0 - вниз, 0 - остановка на лестнице
+   *
+   * Игрок цепляется за лестницу, только если пересекает ее,
+   * прыжок или выход за пределы лестницы возвращают обычное падение
+   */
+  pub fn player_climb(&mut self, id: BodyId, direction: i32) {
+    self.record(Input::PlayerClimb { id, direction });
+
+    let is_on_ladder = match self.rects.get(&id) {
+      Some(rect) => is_on_ladder(&self.cells, rect),
+      None => return
     };
+
+    if let Some(player) = self.players.get_mut(&id) {
+      player.climb(direction.signum() as i8, is_on_ladder);
+    }
+  }
+
+  pub fn try_player_climb(
+    &mut self, id: BodyId, direction: i32
+  ) -> Result<(), PhysError> {
+    self.check_player(id)?;
+    self.player_climb(id, direction);
+    Ok(())
+  }
+}
+
+/**
+ * Проверка, есть ли Block тела или наклонные ячейки среди ячеек,
+ * которые пересекает ограничительный прямоугольник bounds,
+ * но не пересекает текущий прямоугольник тела current
+ *
+ * Ячейки, которые тело уже пересекает (например, наклонная ячейка
+ * под ногами игрока), не считаются препятствием
+ */
+fn has_block_in_new_cells(cells: &Cells, current: &Bounds, bounds: &Bounds) -> bool {
+  let current_x = (current.min_x >> 7)..=((current.max_x - 1) >> 7);
+  let current_y = (current.min_y >> 7)..=((current.max_y - 1) >> 7);
+
+  let min_x = bounds.min_x >> 7;
+  let max_x = (bounds.max_x - 1) >> 7;
+  let min_y = bounds.min_y >> 7;
+  let max_y = (bounds.max_y - 1) >> 7;
+
+  (min_x..=max_x).any(|x_cell| (min_y..=max_y).any(|y_cell| {
+    !(current_x.contains(&x_cell) && current_y.contains(&y_cell))
+    && (cells.is_block(x_cell, y_cell) || cells.is_slope(x_cell, y_cell))
+  }))
+}
+
+/**
+ * Проверка, есть ли Block тела или наклонные ячейки в полосе
+ * над игроком, которую займет тело, если игрок встанет
+ */
+fn has_overhead_block(cells: &Cells, player: &BodyPlayer) -> bool {
+  let stand_bounds = Bounds {
+    min_y: player.y - player.stand_height,
+    ..player.bounds()
+  };
+
+  has_block_in_new_cells(cells, &player.bounds(), &stand_bounds)
+}
+
+/**
+ * Проверка, что тело игрока с новым размером не войдет
+ * в Block тела и наклонные ячейки
+ */
+fn can_resize(cells: &Cells, player: &BodyPlayer, width: i32, height: i32) -> bool {
+  let mut resized = player.clone();
+  resized.set_size(width, height);
+
+  !has_block_in_new_cells(cells, &player.bounds(), &resized.bounds())
+}
+
+/**
+ * Проверка, пересекает ли тело игрока лестницу
+ * (учитываются ячейки по вертикали через середину тела,
+ * включая ячейку, на верхней стороне которой стоит игрок)
+ */
+pub fn is_on_ladder(cells: &Cells, rect: &Rect) -> bool {
+  let x_cell = ((rect.bounds.min_x + rect.bounds.max_x) / 2) >> 7;
+  let min_y = rect.bounds.min_y >> 7;
+  let max_y = rect.bounds.max_y >> 7;
+
+  (min_y..=max_y).any(|y_cell| cells.is_ladder(x_cell, y_cell))
+}
+
+/**
+ * Поиск ячейки жидкости, которую пересекает тело игрока
+ * (учитываются ячейки по вертикали через середину тела),
+ * возвращает идентификатор ячейки
+ */
+pub fn get_liquid_cell(cells: &Cells, rect: &Rect) -> Option<BodyId> {
+  let x_cell = ((rect.bounds.min_x + rect.bounds.max_x) / 2) >> 7;
+  let min_y = rect.bounds.min_y >> 7;
+  let max_y = (rect.bounds.max_y - 1) >> 7;
+
+  (min_y..=max_y)
+    .find(|y_cell| cells.is_liquid(x_cell, *y_cell))
+    .map(|y_cell| cells.get_cell_id(x_cell, y_cell))
+}
+
+/**
+ * Обновление нахождения игроков в жидкости и создание
+ * событий входа в жидкость и выхода из нее
+ */
+pub fn update_swim_players(
+  cells: &Cells, rects: &Rects, players: &mut Bodies<BodyPlayer>,
+  events: &mut Vec<Event>
+) {
+  // Игроки обходятся в порядке идентификаторов,
+  // чтобы порядок событий не зависел от порядка HashMap
+  let mut ids: Vec<BodyId> = players.keys().copied().collect();
+  ids.sort_unstable();
+
+  for id in ids {
+    let (player_body, rect) = match (players.get_mut(&id), rects.get(&id)) {
+      (Some(player_body), Some(rect)) => (player_body, rect),
+      _ => continue
+    };
+
+    let liquid_cell = get_liquid_cell(cells, rect);
+
+    if liquid_cell.is_some() == player_body.is_swim {
+      continue
+    }
+
+    player_body.set_swim(liquid_cell.is_some());
+
+    events.push(Event {
+      class: match liquid_cell {
+        Some(_) => EventClass::LiquidEnter,
+        None => EventClass::LiquidExit
+      },
+      body_id: id,
+      trigger_id: liquid_cell.unwrap_or(0),
+      owner_id: 0,
+      body_data: rect.data,
+      trigger_data: BodyData::default()
+    });
+  }
+}
+
+/**
+ * Отпускание лестниц игроками, которые больше их не пересекают
+ */
+pub fn update_climb_players(
+  cells: &Cells, rects: &Rects, players: &mut Bodies<BodyPlayer>
+) {
+  let mut ids: Vec<BodyId> = players.keys().copied().collect();
+  ids.sort_unstable();
+
+  for id in ids {
+    let player_body = match players.get_mut(&id) {
+      Some(player_body) => player_body,
+      None => continue
+    };
+
+    if !player_body.is_climb {
+      continue
+    }
+
+    let is_on_ladder = match rects.get(&id) {
+      Some(rect) => is_on_ladder(cells, rect),
+      None => continue
+    };
+
+    if !is_on_ladder {
+      player_body.release_ladder();
+    }
+  }
+}
+
+impl World {
+  /**
+   * Проверка существования тела игрока
+   */
+  fn check_player(&self, id: BodyId) -> Result<(), PhysError> {
+    if !self.players.contains_key(&id) {
+      return Err(PhysError::UnknownBody(id))
+    }
+
+    Ok(())
   }
 }
\ No newline at end of file
@@ -0,0 +1,215 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use std::collections::HashMap;
+use crate::body::{ BodyId, BodiesIds, BodyClass };
+use crate::engine::{ Event, EventClass, Rect, Rects };
+use crate::error::PhysError;
+use crate::replay::Input;
+use crate::world::World;
+
+// Максимальный размер сенсора, тело должно находиться
+// не более чем в 4 регионах сетки
+pub const BODY_SENSOR_MAX_SIZE: i32 = 1024;
+
+/**
+ * Тело сенсора
+ *
+ * Неподвижная область, которая не мешает движению, но сообщает
+ * о входе в нее тел игроков и выходе из нее. Хранит список
+ * пересекающих ее тел, чтобы события срабатывали один раз
+ * на каждое пересечение, а не на каждом шаге
+ */
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodySensor {
+  pub overlaps: BodiesIds
+}
+
+/**
+ * Пересечения сенсоров с телами, найденные на текущем шаге
+ */
+pub type SensorsOverlaps = HashMap<BodyId, BodiesIds>;
+
+/**
+ * Идентификаторы тел, входящих в set1 и не входящих в set2,
+ * отсортированные для детерминированного порядка событий
+ */
+fn get_sorted_difference(set1: &BodiesIds, set2: &BodiesIds) -> Vec<BodyId> {
+  let mut result: Vec<BodyId> = set1.difference(set2).copied().collect();
+  result.sort_unstable();
+
+  result
+}
+
+/**
+ * Сравнивает пересечения сенсоров текущего шага с предыдущими
+ * и создает события входа в сенсор и выхода из него
+ *
+ * Сенсоры и тела обходятся в порядке идентификаторов,
+ * чтобы порядок событий не зависел от порядка HashMap
+ */
+pub fn update_sensors_overlaps(
+  rects: &Rects, sensors: &mut HashMap<BodyId, BodySensor>,
+  mut overlaps: SensorsOverlaps, events: &mut Vec<Event>
+) {
+  let mut ids: Vec<BodyId> = sensors.keys().copied().collect();
+  ids.sort_unstable();
+
+  for id in ids {
+    let sensor = match sensors.get_mut(&id) {
+      Some(sensor) => sensor,
+      None => continue
+    };
+
+    let current = overlaps.remove(&id).unwrap_or_default();
+
+    if current.is_empty() && sensor.overlaps.is_empty() {
+      continue
+    }
+
+    let sensor_data = rects.get(&id)
+      .map(|rect| rect.data)
+      .unwrap_or_default();
+
+    for body_id in get_sorted_difference(&current, &sensor.overlaps) {
+      events.push(Event {
+        class: EventClass::SensorEnter,
+        body_id,
+        trigger_id: id,
+        owner_id: 0,
+        body_data: rects.get(&body_id)
+          .map(|rect| rect.data)
+          .unwrap_or_default(),
+        trigger_data: sensor_data
+      });
+    }
+
+    for body_id in get_sorted_difference(&sensor.overlaps, &current) {
+      events.push(Event {
+        class: EventClass::SensorExit,
+        body_id,
+        trigger_id: id,
+        owner_id: 0,
+        body_data: rects.get(&body_id)
+          .map(|rect| rect.data)
+          .unwrap_or_default(),
+        trigger_data: sensor_data
+      });
+    }
+
+    sensor.overlaps = current;
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает тело сенсора шириной width и высотой height,
+   * (x, y) - середина нижней стороны сенсора
+   *
+   * Ширина ограничивается диапазоном от 2 до BODY_SENSOR_MAX_SIZE
+   * (у тела шириной 1 половина ширины была бы нулевой),
+   * высота - от 1 до BODY_SENSOR_MAX_SIZE
+   */
+  pub fn sensor_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> BodyId {
+    self.record(Input::SensorCreate { x, y, width, height });
+
+    let width = width.clamp(2, BODY_SENSOR_MAX_SIZE);
+    let height = height.clamp(1, BODY_SENSOR_MAX_SIZE);
+
+    let id = self.next_body_id();
+
+    self.add_rect(Rect::new(
+      id, BodyClass::Sensor, x, y, width / 2, height
+    ));
+
+    self.sensors.insert(id, BodySensor::default());
+
+    self.ids.insert(id);
+
+    id
+  }
+
+  /**
+   * Создает тело сенсора, возвращает ошибку для ширины меньше 2,
+   * неположительной высоты, размеров больше BODY_SENSOR_MAX_SIZE
+   * и сенсора за границами мира
+   */
+  pub fn try_sensor_create(
+    &mut self, x: i32, y: i32, width: i32, height: i32
+  ) -> Result<BodyId, PhysError> {
+    if width < 2 || height <= 0
+    || width > BODY_SENSOR_MAX_SIZE || height > BODY_SENSOR_MAX_SIZE {
+      return Err(PhysError::InvalidSize { width, height })
+    }
+
+    self.check_position(x, y, width / 2, height)?;
+    Ok(self.sensor_create(x, y, width, height))
+  }
+
+  /**
+   * Создает тело сенсора аналогично sensor_create и сразу присваивает
+   * ему пользовательское значение user_data и метки tags,
+   * поэтому данные доступны в событиях уже на первом шаге
+   */
+  pub fn sensor_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> BodyId {
+    let id = self.sensor_create(x, y, width, height);
+    self.set_body_data(id, user_data, tags);
+    id
+  }
+
+  pub fn try_sensor_create_with_data(
+    &mut self, x: i32, y: i32, width: i32, height: i32,
+    user_data: u64, tags: u32
+  ) -> Result<BodyId, PhysError> {
+    let id = self.try_sensor_create(x, y, width, height)?;
+    self.set_body_data(id, user_data, tags);
+    Ok(id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::engine::{ Direction, EventClass };
+  use crate::world::World;
+
+  #[test]
+  fn sensor_events_fire_once_per_overlap() {
+    let mut world = World::new(60, 60);
+
+    for x in 0..60 {
+      world.block_create(x, 30);
+    }
+
+    let player_id = world.player_create(960, 3840);
+    let sensor_id = world.sensor_create(1500, 3840, 128, 256);
+
+    world.player_run(player_id, Direction::Right);
+
+    let mut enters = 0;
+    let mut exits = 0;
+
+    for _ in 0..180 {
+      for event in world.step(1.0 / 60.0).events {
+        match event.class {
+          EventClass::SensorEnter => enters += 1,
+          EventClass::SensorExit => exits += 1,
+          _ => continue
+        }
+
+        assert_eq!(event.body_id, player_id);
+        assert_eq!(event.trigger_id, sensor_id);
+      }
+    }
+
+    assert_eq!(enters, 1);
+    assert_eq!(exits, 1);
+    assert!(world.rects[&player_id].bounds.min_x > 1564);
+  }
+}
@@ -0,0 +1,58 @@
+/**
+ * Наклонная поверхность, как и Block тело, не имеет отдельного
+ * объекта, форма поверхности хранится в поле slopes в структуре
+ * ячеек Cells
+ *
+ * Игроки ходят по поверхности, поднимаясь и спускаясь плавно,
+ * а не ступенями, с других сторон ячейка ведет себя как Block тело
+ */
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::world::World;
+use crate::body::BodyId;
+use crate::cells::{ Cells, CellFeature, SlopeShape };
+use crate::error::PhysError;
+use crate::replay::Input;
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает наклонную поверхность формы shape в ячейке (x, y),
+   * идентификатор совпадает с идентификатором Block тела ячейки
+   */
+  pub fn slope_create(&mut self, x: i32, y: i32, shape: SlopeShape) -> BodyId {
+    self.record(Input::SlopeCreate { x, y, shape });
+    self.cell_feature_create(x, y, CellFeature::Slope(shape))
+  }
+
+  /**
+   * Создает наклонную поверхность, возвращает ошибку
+   * для ячейки за границами мира
+   */
+  pub fn try_slope_create(
+    &mut self, x: i32, y: i32, shape: SlopeShape
+  ) -> Result<BodyId, PhysError> {
+    self.check_cell(x, y)?;
+    Ok(self.slope_create(x, y, shape))
+  }
+
+  /**
+   * Удаляет наклонную поверхность
+   */
+  pub fn slope_remove(&mut self, id: BodyId) {
+    self.record(Input::SlopeRemove { id });
+    self.cell_feature_remove(id, Cells::is_slope);
+  }
+
+  /**
+   * Удаляет наклонную поверхность, возвращает ошибку,
+   * если в ячейке нет наклонной поверхности
+   */
+  pub fn try_slope_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_cell_feature(id, Cells::is_slope)?;
+    self.slope_remove(id);
+    Ok(())
+  }
+}
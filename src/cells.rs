@@ -1,8 +1,84 @@
-use std::mem::size_of;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::BodyId;
+use crate::engine::BLOCK_SIZE;
+use crate::hash::StateHasher;
+
+/**
+ * Форма наклонной ячейки
+ *
+ * Right - поверхность поднимается слева направо, Left - справа налево.
+ * Наклон 22.5 градуса занимает две ячейки: Low - нижняя половина
+ * подъема, High - верхняя
+ */
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SlopeShape {
+  Right45 = 1,
+  Left45 = 2,
+  Right22Low = 3,
+  Right22High = 4,
+  Left22High = 5,
+  Left22Low = 6
+}
+
+impl SlopeShape {
+  pub fn from_u8(value: u8) -> Option<Self> {
+    let shape = match value {
+      1 => SlopeShape::Right45,
+      2 => SlopeShape::Left45,
+      3 => SlopeShape::Right22Low,
+      4 => SlopeShape::Right22High,
+      5 => SlopeShape::Left22High,
+      6 => SlopeShape::Left22Low,
+      _ => return None
+    };
+
+    Some(shape)
+  }
+
+  /**
+   * Высота поверхности над нижней стороной ячейки
+   * на расстоянии local_x от левой стороны ячейки
+   */
+  pub fn height(&self, local_x: i32) -> i32 {
+    let local_x = local_x.clamp(0, BLOCK_SIZE);
+
+    match self {
+      SlopeShape::Right45 => local_x,
+      SlopeShape::Left45 => BLOCK_SIZE - local_x,
+      SlopeShape::Right22Low => local_x / 2,
+      SlopeShape::Right22High => BLOCK_SIZE / 2 + local_x / 2,
+      SlopeShape::Left22High => BLOCK_SIZE - local_x / 2,
+      SlopeShape::Left22Low => BLOCK_SIZE / 2 - local_x / 2
+    }
+  }
+}
+
+/**
+ * Содержимое ячейки, занимающее ее целиком
+ *
+ * Block тела, односторонние платформы, лестницы, жидкость
+ * и наклонные поверхности не имеют отдельных объектов,
+ * их наличие определяется по статусам ячеек Cells
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CellFeature {
+  Block,
+  OneWay,
+  Ladder,
+  Liquid,
+  Slope(SlopeShape)
+}
 
 /**
  * Структура, хранящая информацию о временно занятых
- * телами ячеек и занятых Block телами ячеек.
+ * телами ячеек, занятых Block телами ячеек и ячеек
+ * с односторонними платформами, лестницами, жидкостью
+ * и наклонными поверхностями.
  *
  * Необходима для определения возможности строительства в ячейке и
  * проверки столкновений с Block телами.
@@ -10,20 +86,52 @@ use std::mem::size_of;
  * Ячейка - квардрат размера одного Block тела (128 на 128 пунктов).
  *
  * Для оптимизации использования памяти данных хранятся в определенных
- * битах массива usize чисел
+ * битах массива usize чисел, формы наклонных ячеек, которым
+ * одного бита недостаточно, хранятся побайтно
  */
-#[derive(Default)]
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cells {
   pub width: i32,
   pub height: i32,
   busy: Vec<usize>,
-  blocks: Vec<usize>
+  blocks: Vec<usize>,
+  // Односторонние платформы, которые останавливают тела
+  // игроков только сверху
+  one_way: Vec<usize>,
+  // Лестницы, по которым тела игроков могут подниматься и спускаться
+  ladders: Vec<usize>,
+  // Жидкость, в которой тела игроков плавают
+  liquids: Vec<usize>,
+  // Формы наклонных ячеек (SlopeShape), 0 - ячейка без наклона
+  slopes: Vec<u8>
 }
 
 /**
  * Определение количества бит в целевой системе
  */
-const TARGET_BITS: usize = size_of::<usize>() * 8;
+const TARGET_BITS: usize = usize::BITS as usize;
+
+/**
+ * Чтение бита ячейки с индексом index
+ */
+fn get_bit(bits: &[usize], index: usize) -> bool {
+  bits[index / TARGET_BITS] & (1 << (index % TARGET_BITS)) != 0
+}
+
+/**
+ * Установка бита ячейки с индексом index
+ */
+fn set_bit(bits: &mut [usize], index: usize, state: bool) {
+  let pos = index / TARGET_BITS;
+  let bit = index % TARGET_BITS;
+
+  if state {
+    bits[pos] |= 1 << bit;
+  } else {
+    bits[pos] &= 1 << bit ^ usize::MAX;
+  };
+}
 
 impl Cells {
   /**
@@ -37,11 +145,15 @@ impl Cells {
       width,
       height,
       busy: vec![0; size],
-      blocks: vec![0; size]
+      blocks: vec![0; size],
+      one_way: vec![0; size],
+      ladders: vec![0; size],
+      liquids: vec![0; size],
+      slopes: vec![0; (width * height) as usize]
     }
   }
 
-  /**
+  /*
    * Установка ячейке статуса занятой
    */
   /*pub fn set_busy(&mut self, x: i32, y: i32, state: bool) {
@@ -56,21 +168,54 @@ impl Cells {
     };
   }*/
 
+  /**
+   * Индекс ячейки в векторах статусов
+   */
+  fn index(&self, x: i32, y: i32) -> usize {
+    (y * self.width + x) as usize
+  }
+
+  /**
+   * Установка ячейке содержимого feature (None - удаление содержимого)
+   *
+   * Ячейка может содержать только одно содержимое, поэтому
+   * предыдущее содержимое ячейки удаляется, ячейка с содержимым
+   * считается занятой
+   */
+  pub fn set_feature(&mut self, x: i32, y: i32, feature: Option<CellFeature>) {
+    let index = self.index(x, y);
+
+    set_bit(&mut self.busy, index, feature.is_some());
+    set_bit(&mut self.blocks, index, feature == Some(CellFeature::Block));
+    set_bit(&mut self.one_way, index, feature == Some(CellFeature::OneWay));
+    set_bit(&mut self.ladders, index, feature == Some(CellFeature::Ladder));
+    set_bit(&mut self.liquids, index, feature == Some(CellFeature::Liquid));
+
+    self.slopes[index] = match feature {
+      Some(CellFeature::Slope(shape)) => shape as u8,
+      _ => 0
+    };
+  }
+
   /**
    * Установка ячейке статуса занятой Block телом
+   *
+   * При снятии статуса содержимое ячейки удаляется, только если
+   * ячейка была занята Block телом, а не другим содержимым
    */
   pub fn set_block(&mut self, x: i32, y: i32, state: bool) {
-    let index = (y * self.width + x) as usize;
-    let pos = index / TARGET_BITS;
-    let bit = index % TARGET_BITS;
-
     if state {
-      self.busy[pos] |= 1 << bit;
-      self.blocks[pos] |= 1 << bit;
-    } else {
-      self.busy[pos] &= 1 << bit ^ usize::MAX;
-      self.blocks[pos] &= 1 << bit ^ usize::MAX;
-    };
+      self.set_feature(x, y, Some(CellFeature::Block));
+    } else if self.contains(x, y) && self.is_block(x, y) {
+      self.set_feature(x, y, None);
+    }
+  }
+
+  /**
+   * Проверка, находится ли ячейка в границах мира
+   */
+  pub fn contains(&self, x: i32, y: i32) -> bool {
+    x >= 0 && x < self.width && y >= 0 && y < self.height
   }
 
   /**
@@ -81,11 +226,7 @@ impl Cells {
       return true
     }
 
-    let index = (y * self.width + x) as usize;
-    let pos = index / TARGET_BITS;
-    let bit = index % TARGET_BITS;
-
-    self.busy[pos] & (1 << bit) != 0
+    get_bit(&self.busy, self.index(x, y))
   }
 
   /**
@@ -96,11 +237,85 @@ impl Cells {
       return true
     }
 
-    let index = (y * self.width + x) as usize;
-    let pos = index / TARGET_BITS;
-    let bit = index % TARGET_BITS;
+    get_bit(&self.blocks, self.index(x, y))
+  }
+
+  /**
+   * Проверка статуса ячейки, занята ли односторонней платформой
+   */
+  pub fn is_one_way(&self, x: i32, y: i32) -> bool {
+    if x < 0 || x >= self.width || y < 0 || y >= self.height {
+      return false
+    }
+
+    get_bit(&self.one_way, self.index(x, y))
+  }
+
+  /**
+   * Проверка статуса ячейки, занята ли лестницей
+   */
+  pub fn is_ladder(&self, x: i32, y: i32) -> bool {
+    if x < 0 || x >= self.width || y < 0 || y >= self.height {
+      return false
+    }
+
+    get_bit(&self.ladders, self.index(x, y))
+  }
+
+  /**
+   * Проверка статуса ячейки, занята ли жидкостью
+   */
+  pub fn is_liquid(&self, x: i32, y: i32) -> bool {
+    if x < 0 || x >= self.width || y < 0 || y >= self.height {
+      return false
+    }
+
+    get_bit(&self.liquids, self.index(x, y))
+  }
+
+  /**
+   * Форма наклонной поверхности ячейки, None для ячейки без наклона
+   */
+  pub fn get_slope(&self, x: i32, y: i32) -> Option<SlopeShape> {
+    if x < 0 || x >= self.width || y < 0 || y >= self.height {
+      return None
+    }
+
+    SlopeShape::from_u8(self.slopes[self.index(x, y)])
+  }
 
-    self.blocks[pos] & (1 << bit) != 0
+  /**
+   * Проверка, есть ли в ячейке наклонная поверхность
+   */
+  pub fn is_slope(&self, x: i32, y: i32) -> bool {
+    self.get_slope(x, y).is_some()
+  }
+
+  /**
+   * Идентификатор ячейки (совпадает с идентификатором Block тела
+   * и другого содержимого ячейки), 0 для ячейки за границами мира
+   */
+  pub fn get_cell_id(&self, x: i32, y: i32) -> BodyId {
+    if !self.contains(x, y) {
+      return 0
+    }
+
+    (y * self.width + x + 1) as BodyId
+  }
+
+  /**
+   * Координаты ячейки по ее идентификатору
+   */
+  pub fn get_cell_position(&self, id: BodyId) -> Option<(i32, i32)> {
+    let index = id as i64 - 1;
+
+    if index < 0 || index >= (self.width * self.height) as i64 {
+      return None
+    }
+
+    let index = index as i32;
+
+    Some((index % self.width, index / self.width))
   }
 
   pub fn can_build(&self, x: i32, y: i32) -> bool {
@@ -108,4 +323,28 @@ impl Cells {
     (self.is_block(x - 1, y) || self.is_block(x + 1, y) ||
      self.is_block(x, y - 1) || self.is_block(x, y + 1))
   }
+
+  /**
+   * Добавление состояния ячеек в контрольную сумму
+   *
+   * Биты записываются побайтно только для существующих ячеек,
+   * чтобы результат не зависел от разрядности usize на платформе
+   */
+  pub fn hash_state(&self, hasher: &mut StateHasher) {
+    let bytes_count = ((self.width * self.height) as usize).div_ceil(8);
+
+    for bits in [
+      &self.busy, &self.blocks, &self.one_way, &self.ladders, &self.liquids
+    ] {
+      let bytes = bits.iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take(bytes_count);
+
+      for byte in bytes {
+        hasher.write_u8(byte);
+      }
+    }
+
+    hasher.write_bytes(&self.slopes);
+  }
 }
\ No newline at end of file
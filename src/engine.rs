@@ -3,8 +3,16 @@ use wasm_bindgen::prelude::*;
 
 use std::cmp::{ min, max };
 use std::collections::HashMap;
-use crate::body::{ BodyId, BodiesIds, BodyClass, Body, Bodies };
-use crate::body::player::{ BODY_PLAYER_HALF_WIDTH, BODY_PLAYER_HEIGHT, BodyPlayer };
+use crate::body::{ BodyId, BodiesIds, BodyClass, BodyData, Body, Bodies };
+use crate::body::bullet::{
+  BODY_BULLET_SIZE, BODY_BULLET_HALF_SIZE, BodyBullet, get_bullet_bounds
+};
+use crate::body::item::BodyItem;
+use crate::body::crates::BodyCrate;
+use crate::body::platform::BodyPlatform;
+use crate::body::player::{
+  BODY_PLAYER_SNAP_DISTANCE, BodyPlayer
+};
 use crate::cells::Cells;
 
 /**
@@ -12,14 +20,55 @@ use crate::cells::Cells;
  * Используется как единица измерения
  */
 pub const BLOCK_SIZE: i32 = 128;
-pub const BLOCK_HALF_SIZE: i32 = BLOCK_SIZE / 2;
+
+/**
+ * Количество микросекунд в секунде
+ *
+ * Все расчеты движения ведутся в целых микросекундах, чтобы
+ * результаты симуляции совпадали на всех целевых платформах
+ * (нативной и wasm32) вне зависимости от реализации
+ * операций с плавающей точкой
+ */
+pub const MICROS_IN_SECOND: i64 = 1_000_000;
+
+/**
+ * Перевод длительности в секундах в целые микросекунды
+ */
+pub fn seconds_to_micros(seconds: f32) -> i32 {
+  (seconds as f64 * MICROS_IN_SECOND as f64) as i32
+}
+
+/**
+ * Расстояние, пройденное за delta микросекунд со скоростью
+ * speed пунктов в секунду, с отбрасыванием дробной части
+ */
+pub fn get_distance(speed: i32, delta: i32) -> i32 {
+  (speed as i64 * delta as i64 / MICROS_IN_SECOND) as i32
+}
+
+/**
+ * Смещение по параболе падения с ускорением gravity
+ * (пунктов в секунду за секунду) за время timer (в микросекундах)
+ *
+ * Расчет ведется в целых числах с отбрасыванием дробной части,
+ * промежуточное значение в i128, чтобы квадрат времени
+ * не переполнялся при долгом падении
+ */
+pub fn get_gravity_offset(gravity: i64, timer: i32) -> i32 {
+  let timer = timer as i128;
+  let micros_in_second = MICROS_IN_SECOND as i128;
+
+  (gravity as i128 * timer * timer
+    / (micros_in_second * micros_in_second)) as i32
+}
 
 /**
  * Ограничительный прямоугольник тела
  * Содержит максимальные и минимальные координаты
  * сторон прямоугольника
  */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
   pub min_x: i32,
   pub max_x: i32,
@@ -32,50 +81,66 @@ pub struct Bounds {
  */
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
+  #[default]
   None = 0,
   Left = 1,
   Right = 2
 }
 
-impl Default for Direction {
-  fn default() -> Self {
-    Self::None
-  }
-}
-
 /**
  * Тип события
  */
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventClass {
   // Тело вышло за границы мира
   OutOfWorld = 0,
-  // Пересечение тела игрока с сенсором
-  Sensor = 1,
+  // Тело игрока вошло в сенсор
+  SensorEnter = 1,
   // Пересечение тела игрока с телом предмета
-  Item = 2
+  Item = 2,
+  // Тело игрока вышло из сенсора
+  SensorExit = 3,
+  // Попадание пули в тело игрока
+  BulletHit = 4,
+  // Попадание пули в Block тело
+  BulletBlock = 5,
+  // Тело игрока вошло в жидкость (причина - ячейка жидкости)
+  LiquidEnter = 6,
+  // Тело игрока вышло из жидкости
+  LiquidExit = 7
 }
 
 /**
  * Событие физического мира
  */
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event {
   // Тип события
   pub class: EventClass,
   // Идентификатор тела, для которого сработало событие
   pub body_id: BodyId,
   // Идентификатор тела - причины события
-  pub trigger_id: BodyId
+  pub trigger_id: BodyId,
+  // Идентификатор владельца тела - причины события
+  // (тело, выпустившее пулю), 0 для остальных событий
+  pub owner_id: BodyId,
+  // Пользовательские данные тела, для которого сработало событие
+  pub body_data: BodyData,
+  // Пользовательские данные тела - причины события
+  pub trigger_data: BodyData
 }
 
 /**
  * Обновление позиции тела
  */
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionUpdate {
   pub id: BodyId,
   pub x: i32,
@@ -85,13 +150,15 @@ pub struct PositionUpdate {
 /**
  * Прямоугольник тела для сетки
  */
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
   pub id: BodyId,
   pub class: BodyClass,
   pub bounds: Bounds,
   pub regions: RegionsIds,
-  pub is_updated: bool
+  pub is_updated: bool,
+  pub data: BodyData
 }
 
 impl Rect {
@@ -99,8 +166,8 @@ impl Rect {
     id: u32, class: BodyClass, x: i32, y: i32, half_width: i32, height: i32
   ) -> Self {
     Self {
-      id: id,
-      class: class,
+      id,
+      class,
       bounds: Bounds {
         min_x: x - half_width,
         max_x: x + half_width,
@@ -108,13 +175,20 @@ impl Rect {
         max_y: y
       },
       regions: RegionsIds::default(),
-      is_updated: false
+      is_updated: false,
+      data: BodyData::default()
     }
   }
 }
 
 pub type Rects = HashMap<BodyId, Rect>;
 
+/**
+ * Препятствия (платформы и ящики), пересекающие
+ * тела игроков на текущем шаге
+ */
+pub type ObstaclesContacts = HashMap<BodyId, Vec<BodyId>>;
+
 /**
  * Идентификатор региона и группа идентификаторов
  */
@@ -124,7 +198,8 @@ pub type RegionsIds = [RegionId; 4];
 /**
  * Результаты обновления физического мира
  */
-#[derive(Debug)]
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UpdateResults {
   // Список событий
   pub events: Vec<Event>,
@@ -132,6 +207,24 @@ pub struct UpdateResults {
   pub positions_updates: Vec<PositionUpdate>
 }
 
+impl UpdateResults {
+  /**
+   * Добавляет результаты следующего шага: события дописываются в конец,
+   * для каждого тела сохраняется только последняя позиция
+   */
+  pub fn append(&mut self, other: UpdateResults) {
+    self.events.extend(other.events);
+
+    for position_update in other.positions_updates {
+      match self.positions_updates.iter_mut()
+        .find(|existing| existing.id == position_update.id) {
+        Some(existing) => *existing = position_update,
+        None => self.positions_updates.push(position_update)
+      }
+    }
+  }
+}
+
 /**
  * Самый, насколько это возможно, просто вектор
  */
@@ -156,15 +249,28 @@ pub fn get_bounds_intersection(bounds1: &Bounds, bounds2: &Bounds) -> Vector {
   }
 }
 
+/**
+ * Проверка пересечения двух ограничительных прямоугольников
+ * (касание сторонами пересечением не считается)
+ */
+pub fn is_bounds_intersect(bounds1: &Bounds, bounds2: &Bounds) -> bool {
+  let intersection = get_bounds_intersection(bounds1, bounds2);
+
+  intersection.x > 0 && intersection.y > 0
+}
+
 pub fn update_positions_typed<T: Body>(
-  delta: f32, world_width: i32, world_height: i32,
+  delta: i32, world_width: i32, world_height: i32,
   rects: &mut Rects, bodies: &mut Bodies<T>,
   ids_to_remove: &mut BodiesIds, events: &mut Vec<Event>
 ) {
   for (id, body) in bodies.iter_mut() {
-    let mut rect = rects.get_mut(id).unwrap();
+    let rect = match rects.get_mut(id) {
+      Some(rect) => rect,
+      None => continue
+    };
 
-    body.update(delta, &mut rect);
+    body.update(delta, rect);
 
     if rect.bounds.min_x < 0
     || rect.bounds.max_x > world_width
@@ -174,109 +280,424 @@ pub fn update_positions_typed<T: Body>(
       events.push(Event {
         class: EventClass::OutOfWorld,
         body_id: *id,
-        trigger_id: 0
+        trigger_id: 0,
+        owner_id: 0,
+        body_data: rect.data,
+        trigger_data: BodyData::default()
       });
     }
   }
 }
 
+/**
+ * Коррекция позиции тела игрока при пересечении с препятствием
+ * (Block телом или платформой), None при отсутствии пересечения
+ *
+ * prev_bounds и obstacle_prev_bounds - ограничительные прямоугольники
+ * игрока и препятствия на предыдущем шаге, по ним определяется
+ * сторона, с которой игрок вошел в препятствие
+ */
+fn get_player_correction(
+  player_body: &BodyPlayer, bounds: &Bounds, prev_bounds: &Bounds,
+  obstacle_bounds: &Bounds, obstacle_prev_bounds: &Bounds
+) -> Option<Vector> {
+  let intersection = get_bounds_intersection(bounds, obstacle_bounds);
+  if intersection.x <= 0 || intersection.y <= 0 {
+    return None
+  }
+
+  let mut correction_x = intersection.x;
+  let mut correction_y = intersection.y;
+
+  if bounds.max_y < obstacle_bounds.max_y {
+    correction_y = -correction_y;
+  }
+  if player_body.x < (obstacle_bounds.min_x + obstacle_bounds.max_x) / 2 {
+    correction_x = -correction_x;
+  }
+
+  let prev_intersection = get_bounds_intersection(
+    prev_bounds, obstacle_prev_bounds
+  );
+
+  if prev_intersection.x > 0 {
+    correction_x = 0;
+  }
+  else if prev_intersection.y > 0 {
+    correction_y = 0;
+  }
+  else {
+    if player_body.force_x != 0 {
+      correction_x = 0;
+    }
+
+    if player_body.is_fall || player_body.is_jump {
+      correction_y = 0;
+    }
+  }
+
+  Some(Vector { x: correction_x, y: correction_y })
+}
+
+/**
+ * Поиск наклонной поверхности или верхней стороны Block тела
+ * под точкой (x, y) на расстоянии не более BODY_PLAYER_SNAP_DISTANCE,
+ * возвращает координату поверхности
+ */
+fn get_snap_ground(cells: &Cells, x: i32, y: i32) -> Option<i32> {
+  let x_cell = x >> 7;
+
+  for y_cell in (y >> 7)..=((y + BODY_PLAYER_SNAP_DISTANCE) >> 7) {
+    if let Some(shape) = cells.get_slope(x_cell, y_cell) {
+      let ground_y = (y_cell + 1) * BLOCK_SIZE
+        - shape.height(x - x_cell * BLOCK_SIZE);
+
+      if ground_y > y && ground_y <= y + BODY_PLAYER_SNAP_DISTANCE {
+        return Some(ground_y)
+      }
+    }
+
+    if cells.is_block(x_cell, y_cell) {
+      let ground_y = y_cell * BLOCK_SIZE;
+
+      if ground_y > y && ground_y <= y + BODY_PLAYER_SNAP_DISTANCE {
+        return Some(ground_y)
+      }
+
+      return None
+    }
+  }
+
+  None
+}
+
 pub fn update_correct_players(
   cells: &Cells, rects: &mut Rects,
-  players: &mut Bodies<BodyPlayer>
+  players: &mut Bodies<BodyPlayer>,
+  platforms: &Bodies<BodyPlatform>, crates: &Bodies<BodyCrate>,
+  obstacles_contacts: &ObstaclesContacts
 ) {
   for (id, player_body) in players.iter_mut() {
-    let rect = rects.get_mut(id).unwrap();
+    let rect = match rects.get_mut(id) {
+      Some(rect) => rect,
+      None => continue
+    };
+
+    // Стоящий игрок при спуске по наклонной поверхности прижимается
+    // к ней с погружением на 1 пункт, как при обычном шаге на земле,
+    // поэтому коррекция ниже оставляет его на земле
+    if player_body.is_on_ground && !player_body.is_jump && !player_body.is_climb {
+      if let Some(ground_y) = get_snap_ground(cells, player_body.x, player_body.y) {
+        player_body.y = ground_y + 1;
+        rect.bounds = player_body.bounds();
+      }
+    }
 
     let min_x = rect.bounds.min_x >> 7;
     let max_x = rect.bounds.max_x >> 7;
     let min_y = rect.bounds.min_y >> 7;
     let max_y = rect.bounds.max_y >> 7;
 
+    let prev_bounds = player_body.prev_bounds();
+
     let mut correction = Vector { x: 0, y: 0 };
+    // Платформа, давшая вертикальную коррекцию (или 0)
+    let mut ground_platform = 0;
+
+    // Верхняя сторона наклонной ячейки под серединой игрока, Block тела
+    // не выше нее (опора следующей наклонной ячейки, пол под наклоном)
+    // не мешают игроку подниматься и спускаться по поверхности
+    let foot_y_cell = (player_body.y - 1) >> 7;
+    let slope_top = cells.get_slope(player_body.x >> 7, foot_y_cell)
+      .map(|_| foot_y_cell * BLOCK_SIZE);
 
     for x_cell in min_x..=max_x {
       for y_cell in min_y..=max_y {
-        if !cells.is_block(x_cell, y_cell) {
-          continue
-        }
-
         let x = x_cell * BLOCK_SIZE;
         let y = y_cell * BLOCK_SIZE;
-        let block_bounds = Bounds {
-          min_x: x,
-          max_x: x + BLOCK_SIZE,
-          min_y: y,
-          max_y: y + BLOCK_SIZE
-        };
 
-        let intersection = get_bounds_intersection(
-          &rect.bounds, &block_bounds
-        );
-        if intersection.x <= 0 || intersection.y <= 0 {
-          continue
-        }
+        // Односторонняя платформа останавливает игрока, только если
+        // на предыдущем шаге он был не ниже ее верхней стороны,
+        // поэтому игрок в прыжке проходит сквозь нее снизу.
+        // Верхняя ячейка лестницы работает так же для игроков,
+        // которые не держатся за лестницу, чтобы на нее можно было встать
+        let is_ladder_top = !player_body.is_climb
+          && cells.is_ladder(x_cell, y_cell)
+          && !cells.is_ladder(x_cell, y_cell - 1);
+
+        if cells.is_one_way(x_cell, y_cell) || is_ladder_top {
+          if rect.bounds.max_y <= y
+          || rect.bounds.max_x <= x
+          || rect.bounds.min_x >= x + BLOCK_SIZE
+          || player_body.prev_y > y
+          || (player_body.is_jump && player_body.move_dir_y < 0)
+          || (player_body.drop_down && y <= player_body.last_ground_y) {
+            continue
+          }
 
-        let mut correction_x = intersection.x;
-        let mut correction_y = intersection.y;
+          let correction_y = y - player_body.y;
 
-        if rect.bounds.max_y < block_bounds.max_y {
-          correction_y = -correction_y;
-        }
-        if player_body.x < x + BLOCK_HALF_SIZE {
-          correction_x = -correction_x;
+          if correction_y.abs() > correction.y.abs() {
+            correction.y = correction_y;
+            ground_platform = 0;
+          }
+
+          continue
         }
 
-        let prev_bounds = Bounds {
-          min_x: player_body.prev_x - BODY_PLAYER_HALF_WIDTH,
-          max_x: player_body.prev_x + BODY_PLAYER_HALF_WIDTH,
-          min_y: player_body.prev_y - BODY_PLAYER_HEIGHT,
-          max_y: player_body.prev_y
-        };
+        // Наклонная поверхность проверяется по середине нижней
+        // стороны игрока, если игрок пришел сверху, иначе ячейка
+        // сталкивается с игроком как Block тело
+        if let Some(shape) = cells.get_slope(x_cell, y_cell) {
+          if player_body.x >> 7 != x_cell {
+            continue
+          }
 
-        let prev_intersection = get_bounds_intersection(
-          &prev_bounds, &block_bounds
-        );
+          let bottom = y + BLOCK_SIZE;
+          let ground_y = bottom - shape.height(player_body.x - x);
 
-        if prev_intersection.x > 0 {
-          correction_x = 0;
-        }
-        else if prev_intersection.y > 0 {
-          correction_y = 0;
-        }
-        else {
-          if player_body.force_x != 0.0 {
-            correction_x = 0;
+          if player_body.y <= ground_y {
+            continue
           }
 
-          if player_body.is_fall || player_body.is_jump {
-            correction_y = 0;
+          // При переходе стоящего игрока из соседней ячейки высота
+          // поверхности на границе ячеек может отличаться на несколько
+          // пунктов из-за округления, поэтому допускается запас
+          let prev_ground_y = bottom - shape.height(player_body.prev_x - x);
+          let tolerance = if player_body.is_on_ground { BODY_PLAYER_SNAP_DISTANCE } else { 0 };
+
+          if player_body.prev_y <= prev_ground_y + tolerance
+          && !(player_body.is_jump && player_body.move_dir_y < 0) {
+            let correction_y = ground_y - player_body.y;
+
+            if correction_y.abs() > correction.y.abs() {
+              correction.y = correction_y;
+              ground_platform = 0;
+            }
+
+            continue
           }
+        } else if !cells.is_block(x_cell, y_cell)
+        || slope_top.is_some_and(|slope_top| y >= slope_top) {
+          continue
         }
 
-        if correction_x.abs() > correction.x.abs() {
-          correction.x = correction_x;
+        let block_bounds = Bounds {
+          min_x: x,
+          max_x: x + BLOCK_SIZE,
+          min_y: y,
+          max_y: y + BLOCK_SIZE
+        };
+
+        let block_correction = match get_player_correction(
+          player_body, &rect.bounds, &prev_bounds,
+          &block_bounds, &block_bounds
+        ) {
+          Some(block_correction) => block_correction,
+          None => continue
+        };
+
+        if block_correction.x.abs() > correction.x.abs() {
+          correction.x = block_correction.x;
         }
-        if correction_y.abs() > correction.y.abs() {
-          correction.y = correction_y;
+        if block_correction.y.abs() > correction.y.abs() {
+          correction.y = block_correction.y;
+          ground_platform = 0;
         }
       }
     }
 
+    for obstacle_id in obstacles_contacts.get(id).into_iter().flatten() {
+      // Игрок переносится только платформами, поэтому
+      // для ящиков идентификатор опоры не запоминается
+      let (obstacle_bounds, obstacle_prev_bounds, platform_id) = match (
+        platforms.get(obstacle_id), crates.get(obstacle_id)
+      ) {
+        (Some(platform), _) => {
+          (platform.bounds(), platform.prev_bounds(), *obstacle_id)
+        },
+        (_, Some(crate_body)) => {
+          (crate_body.bounds(), crate_body.prev_bounds(), 0)
+        },
+        _ => continue
+      };
+
+      let obstacle_correction = match get_player_correction(
+        player_body, &rect.bounds, &prev_bounds,
+        &obstacle_bounds, &obstacle_prev_bounds
+      ) {
+        Some(obstacle_correction) => obstacle_correction,
+        None => continue
+      };
+
+      if obstacle_correction.x.abs() > correction.x.abs() {
+        correction.x = obstacle_correction.x;
+      }
+      if obstacle_correction.y.abs() > correction.y.abs() {
+        correction.y = obstacle_correction.y;
+        ground_platform = platform_id;
+      }
+    }
+
     if correction.x == 0 && correction.y == 0 {
       continue
     }
 
+    player_body.ground_platform = if correction.y < 0 { ground_platform } else { 0 };
     player_body.update_correction(&correction);
 
-    let new_x = player_body.x + correction.x;
-    let new_y = player_body.y + correction.y;
+    player_body.x += correction.x;
+    player_body.y += correction.y;
+
+    rect.bounds = player_body.bounds();
+  }
+}
+
+/**
+ * Приземление падающих предметов на Block тела
+ *
+ * Предмет останавливается на верхней стороне первого блока,
+ * который его нижняя сторона пересекла за шаг, поэтому предмет
+ * не проваливается сквозь блок даже при большом смещении за шаг
+ */
+pub fn update_correct_items(
+  cells: &Cells, rects: &mut Rects, items: &mut Bodies<BodyItem>
+) {
+  for (id, item_body) in items.iter_mut() {
+    if item_body.is_on_ground || item_body.y == item_body.prev_y {
+      continue
+    }
+
+    let rect = match rects.get_mut(id) {
+      Some(rect) => rect,
+      None => continue
+    };
+
+    let min_x = rect.bounds.min_x >> 7;
+    let max_x = (rect.bounds.max_x - 1) >> 7;
+
+    // Первый ряд ячеек, верхняя сторона которых не выше
+    // нижней стороны предмета на предыдущем шаге
+    let min_y = (item_body.prev_y + BLOCK_SIZE - 1) >> 7;
+    let max_y = item_body.y >> 7;
+
+    let ground_y = (min_y..=max_y).find(|y_cell| {
+      (min_x..=max_x).any(|x_cell| {
+        cells.is_block(x_cell, *y_cell) || cells.is_one_way(x_cell, *y_cell)
+      })
+    });
+
+    if let Some(y_cell) = ground_y {
+      item_body.land(y_cell * BLOCK_SIZE);
+      item_body.update_rect(rect);
+    }
+  }
+}
+
+/**
+ * Уничтожение пуль, попавших в Block тела или в тела игроков
+ *
+ * Путь пули за шаг проверяется с шагом в размер пули,
+ * чтобы быстрая пуля не пролетала сквозь блок или игрока.
+ * Пуля поражает первое препятствие на своем пути: игрок
+ * за блоком не поражается, а из нескольких игроков в одной
+ * точке пути поражается игрок с меньшим идентификатором
+ */
+pub fn update_correct_bullets(
+  cells: &Cells, rects: &Rects, bullets: &mut Bodies<BodyBullet>,
+  players: &Bodies<BodyPlayer>, ids_to_remove: &mut BodiesIds,
+  events: &mut Vec<Event>
+) {
+  let mut ids: Vec<BodyId> = bullets.keys().copied().collect();
+  ids.sort_unstable();
 
-    player_body.x = new_x;
-    player_body.y = new_y;
+  for id in ids {
+    if ids_to_remove.contains(&id) {
+      continue
+    }
 
-    rect.bounds.min_x = new_x - BODY_PLAYER_HALF_WIDTH;
-    rect.bounds.max_x = new_x + BODY_PLAYER_HALF_WIDTH;
-    rect.bounds.min_y = new_y - BODY_PLAYER_HEIGHT;
-    rect.bounds.max_y = new_y;
+    let bullet_body = match bullets.get_mut(&id) {
+      Some(bullet_body) => bullet_body,
+      None => continue
+    };
+
+    let bullet_data = rects.get(&id)
+      .map(|rect| rect.data)
+      .unwrap_or_default();
+
+    // Игроки, которых может задеть пуля на пути за шаг
+    let path_bounds = Bounds {
+      min_x: min(bullet_body.prev_x, bullet_body.x) - BODY_BULLET_HALF_SIZE,
+      max_x: max(bullet_body.prev_x, bullet_body.x) + BODY_BULLET_HALF_SIZE,
+      min_y: min(bullet_body.prev_y, bullet_body.y) - BODY_BULLET_SIZE,
+      max_y: max(bullet_body.prev_y, bullet_body.y)
+    };
+
+    let mut targets: Vec<&Rect> = players.keys()
+      .filter(|player_id| {
+        **player_id != bullet_body.owner && !ids_to_remove.contains(player_id)
+      })
+      .filter_map(|player_id| rects.get(player_id))
+      .filter(|rect| is_bounds_intersect(&rect.bounds, &path_bounds))
+      .collect();
+
+    targets.sort_unstable_by_key(|rect| rect.id);
+
+    let delta_x = bullet_body.x - bullet_body.prev_x;
+    let delta_y = bullet_body.y - bullet_body.prev_y;
+    let steps = max(delta_x.abs(), delta_y.abs()) / BODY_BULLET_SIZE + 1;
+
+    for step in 1..=steps {
+      let x = bullet_body.prev_x + delta_x * step / steps;
+      let y = bullet_body.prev_y + delta_y * step / steps;
+
+      let bounds = get_bullet_bounds(x, y);
+
+      let min_x = bounds.min_x >> 7;
+      let max_x = (bounds.max_x - 1) >> 7;
+      let min_y = bounds.min_y >> 7;
+      let max_y = (bounds.max_y - 1) >> 7;
+
+      let block = (min_x..=max_x)
+        .flat_map(|x_cell| (min_y..=max_y).map(move |y_cell| (x_cell, y_cell)))
+        .find(|(x_cell, y_cell)| cells.is_block(*x_cell, *y_cell));
+
+      if let Some((x_cell, y_cell)) = block {
+        bullet_body.x = x;
+        bullet_body.y = y;
+
+        ids_to_remove.insert(id);
+        events.push(Event {
+          class: EventClass::BulletBlock,
+          body_id: id,
+          trigger_id: cells.get_cell_id(x_cell, y_cell),
+          owner_id: bullet_body.owner,
+          body_data: bullet_data,
+          trigger_data: BodyData::default()
+        });
+
+        break
+      }
+
+      let target = targets.iter()
+        .find(|rect| is_bounds_intersect(&rect.bounds, &bounds));
+
+      if let Some(target) = target {
+        bullet_body.x = x;
+        bullet_body.y = y;
+
+        ids_to_remove.insert(id);
+        events.push(Event {
+          class: EventClass::BulletHit,
+          body_id: target.id,
+          trigger_id: id,
+          owner_id: bullet_body.owner,
+          body_data: target.data,
+          trigger_data: bullet_data
+        });
+
+        break
+      }
+    }
   }
-}
\ No newline at end of file
+}
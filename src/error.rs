@@ -0,0 +1,49 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use std::fmt;
+use crate::body::BodyId;
+
+/**
+ * Ошибки публичных методов физического мира
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PhysError {
+  // Тело с указанным идентификатором не существует
+  UnknownBody(BodyId),
+  // Координаты ячейки за границами мира
+  CellOutOfWorld { x: i32, y: i32 },
+  // Тело в указанной позиции выходит за границы мира
+  PositionOutOfWorld { x: i32, y: i32 },
+  // Недопустимый размер тела
+  InvalidSize { width: i32, height: i32 }
+}
+
+impl fmt::Display for PhysError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PhysError::UnknownBody(id) => write!(f, "unknown body {}", id),
+      PhysError::CellOutOfWorld { x, y } => {
+        write!(f, "cell ({}, {}) is out of world", x, y)
+      },
+      PhysError::PositionOutOfWorld { x, y } => {
+        write!(f, "body at ({}, {}) is out of world", x, y)
+      },
+      PhysError::InvalidSize { width, height } => {
+        write!(f, "invalid body size {}x{}", width, height)
+      }
+    }
+  }
+}
+
+impl std::error::Error for PhysError {}
+
+/**
+ * В wasm ошибки пробрасываются в JS как исключения Error
+ */
+#[cfg(target_arch = "wasm32")]
+impl From<PhysError> for JsValue {
+  fn from(error: PhysError) -> Self {
+    js_sys::Error::new(&error.to_string()).into()
+  }
+}
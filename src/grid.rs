@@ -1,10 +1,11 @@
 use std::collections::{ HashMap, HashSet };
-use crate::body::{ BodyId, BodyClass };
+use crate::body::{ BodyId, BodiesIds, BodyClass };
 use crate::engine::{ Bounds, Rect, Rects, RegionId, RegionsIds };
 
 type PairId = u64;
 
-#[derive(Default, Debug)]
+#[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pair {
   pub id1: BodyId,
   pub id2: BodyId,
@@ -37,41 +38,55 @@ fn get_pair_id(id1: BodyId, id2: BodyId) -> PairId {
 /**
  * Битовые маски классов тел
  */
-const BODIES_CATEGORIES: [u8; 6] = [
+const BODIES_CATEGORIES: [u8; 8] = [
   0b00000001,
   0b00000010,
   0b00000100,
   0b00001000,
   0b00010000,
-  0b00100000
+  0b00100000,
+  0b01000000,
+  0b10000000
 ];
 
 /**
  * Битовые фильтры возможности столкновений классов тел
  */
-const BODIES_FILTERS: [u8; 6] = [
+const BODIES_FILTERS: [u8; 8] = [
   0b00111100,
   0b00000100,
-  0b00111011,
+  0b11011011,
   0b00000101,
   0b00000101,
-  0b00000101
+  0b00000001,
+  0b00000100,
+  0b00000100
 ];
 
 /**
  * Определяет возможность столкновения тел, в зависимости от их класса
  *
  * Таблица возможности столкновений классов тел:
- * +--------+--------+--------+--------+--------+--------+--------+
- * |        | Fixed  | Sensor | Player | Ray    | Item   | Bullet |
- * +--------+--------+--------+--------+--------+--------+--------+
- * | Fixed  |        |        |   XX   |   XX   |   XX   |   XX   |
- * | Sensor |        |        |   XX   |        |        |        |
- * | Player |   XX   |   XX   |        |   XX   |   XX   |   XX   |
- * | Ray    |   XX   |        |   XX   |        |        |        |
- * | Item   |   XX   |        |   XX   |        |        |        |
- * | Bullet |   XX   |        |   XX   |        |        |        |
- * +--------+--------+--------+--------+--------+--------+--------+
+ * +----------+--------+--------+--------+--------+--------+--------+----------+--------+
+ * |          | Fixed  | Sensor | Player | Ray    | Item   | Bullet | Platform | Crate  |
+ * +----------+--------+--------+--------+--------+--------+--------+----------+--------+
+ * | Fixed    |        |        |   XX   |   XX   |   XX   |   XX   |          |        |
+ * | Sensor   |        |        |   XX   |        |        |        |          |        |
+ * | Player   |   XX   |   XX   |        |   XX   |   XX   |        |    XX    |   XX   |
+ * | Ray      |   XX   |        |   XX   |        |        |        |          |        |
+ * | Item     |   XX   |        |   XX   |        |        |        |          |        |
+ * | Bullet   |   XX   |        |        |        |        |        |          |        |
+ * | Platform |        |        |   XX   |        |        |        |          |        |
+ * | Crate    |        |        |   XX   |        |        |        |          |        |
+ * +----------+--------+--------+--------+--------+--------+--------+----------+--------+
+ *
+ * Ящики между собой через пары не проверяются: ящики, стоящие
+ * друг на друге, только касаются, поэтому опора и столкновения
+ * ящиков ищутся по регионам сетки (см. get_bodies_by_bounds)
+ *
+ * Пули с игроками через пары не проверяются: пересечение пули
+ * с игроком проверяется по всему пути пули за шаг
+ * (см. update_correct_bullets)
  */
 fn can_collide(class1: &BodyClass, class2: &BodyClass) -> bool {
   if class1 == class2 {
@@ -122,6 +137,14 @@ fn get_regions_by_bounds(bounds: &Bounds) -> RegionsIds {
   regions
 }
 
+/**
+ * Возвращает идентификатор региона, содержащего точку
+ * (см. описание функции get_regions_by_bounds)
+ */
+pub fn get_region_by_point(x: i32, y: i32) -> RegionId {
+  ((y >> 10) << 16) + (x >> 10) + 1
+}
+
 /**
  * Сетка
  *
@@ -133,7 +156,8 @@ fn get_regions_by_bounds(bounds: &Bounds) -> RegionsIds {
  * Максимум регионов по ширине и высоте 256 (см. описание
  * функции get_regions_by_bounds)
  */
-#[derive(Default)]
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Grid {
   // Пары идентификаторов тел с возможностью столкновения
   pub pairs: HashMap<PairId, Pair>,
@@ -167,16 +191,13 @@ impl Grid {
 
         let pair_id = get_pair_id(rect.id, other_rect.id);
 
-        if self.pairs.contains_key(&pair_id) {
-          let pair = self.pairs.get_mut(&pair_id).unwrap();
-          pair.count = pair.count + 1;
-        } else {
-          self.pairs.insert(pair_id, Pair {
+        self.pairs.entry(pair_id)
+          .and_modify(|pair| pair.count += 1)
+          .or_insert(Pair {
             id1: rect.id,
             id2: other_rect.id,
             count: 1
           });
-        }
       }
     }
   }
@@ -196,7 +217,7 @@ impl Grid {
             if pair.count == 1 {
               self.pairs.remove(&pair_id);
             } else {
-              pair.count = pair.count - 1;
+              pair.count -= 1;
             }
           }
         }
@@ -204,11 +225,36 @@ impl Grid {
     }
   }
 
+  /**
+   * Идентификаторы тел, находящихся в регионе
+   */
+  pub fn get_region_bodies(&self, region: RegionId) -> Option<&HashSet<BodyId>> {
+    self.hash.get(&region)
+  }
+
+  /**
+   * Идентификаторы тел, находящихся в регионах,
+   * которые пересекает ограничительный прямоугольник
+   */
+  pub fn get_bodies_by_bounds(&self, bounds: &Bounds) -> BodiesIds {
+    let mut result = BodiesIds::default();
+
+    for x in (bounds.min_x >> 10)..=(bounds.max_x >> 10) {
+      for y in (bounds.min_y >> 10)..=(bounds.max_y >> 10) {
+        if let Some(ids) = self.hash.get(&((y << 16) + x + 1)) {
+          result.extend(ids);
+        }
+      }
+    }
+
+    result
+  }
+
   /**
    * Добавление тела в сетку
    */
   pub fn add(&mut self, id: BodyId, rects: &mut Rects) {
-    let mut rect = rects.get_mut(&id).unwrap();
+    let rect = rects.get_mut(&id).unwrap();
 
     let regions = get_regions_by_bounds(&rect.bounds);
     rect.regions = regions;
@@ -231,7 +277,7 @@ impl Grid {
    * Обновление тела в сетке
    */
   pub fn update(&mut self, id: BodyId, rects: &mut Rects) {
-    let mut rect = rects.get_mut(&id).unwrap();
+    let rect = rects.get_mut(&id).unwrap();
 
     if !rect.is_updated {
       return
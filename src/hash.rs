@@ -0,0 +1,123 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::BodyId;
+use crate::world::World;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/**
+ * Хеш-функция FNV-1a (64 бита) для контрольной суммы состояния мира
+ *
+ * Используется вместо стандартного Hasher, так как алгоритм
+ * стандартной библиотеки не гарантирует одинаковый результат
+ * между версиями компилятора и платформами.
+ * Все числа записываются в порядке little-endian.
+ */
+pub struct StateHasher {
+  state: u64
+}
+
+impl Default for StateHasher {
+  fn default() -> Self {
+    Self {
+      state: FNV_OFFSET_BASIS
+    }
+  }
+}
+
+impl StateHasher {
+  pub fn write_bytes(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.state ^= *byte as u64;
+      self.state = self.state.wrapping_mul(FNV_PRIME);
+    }
+  }
+
+  pub fn write_bool(&mut self, value: bool) {
+    self.write_bytes(&[value as u8]);
+  }
+
+  pub fn write_i8(&mut self, value: i8) {
+    self.write_bytes(&value.to_le_bytes());
+  }
+
+  pub fn write_u8(&mut self, value: u8) {
+    self.write_bytes(&[value]);
+  }
+
+  pub fn write_i32(&mut self, value: i32) {
+    self.write_bytes(&value.to_le_bytes());
+  }
+
+  pub fn write_u32(&mut self, value: u32) {
+    self.write_bytes(&value.to_le_bytes());
+  }
+
+  pub fn finish(&self) -> u64 {
+    self.state
+  }
+}
+
+/**
+ * Возвращает отсортированный список идентификаторов,
+ * так как порядок обхода HashMap не определен
+ */
+fn sorted_ids<'a, I: Iterator<Item = &'a BodyId>>(ids: I) -> Vec<BodyId> {
+  let mut ids: Vec<BodyId> = ids.copied().collect();
+  ids.sort_unstable();
+  ids
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Контрольная сумма состояния мира
+   *
+   * Включает позиции всех тел, состояние игроков и ячейки блоков.
+   * Одинаковое состояние дает одинаковый хеш на всех платформах,
+   * что позволяет сравнивать хеши клиента и сервера на каждом шаге
+   * для раннего обнаружения рассинхронизации
+   */
+  pub fn state_hash(&self) -> u64 {
+    let mut hasher = StateHasher::default();
+
+    self.cells.hash_state(&mut hasher);
+
+    for id in sorted_ids(self.players.keys()) {
+      hasher.write_u32(id);
+      self.players[&id].hash_state(&mut hasher);
+    }
+
+    for id in sorted_ids(self.items.keys()) {
+      hasher.write_u32(id);
+      self.items[&id].hash_state(&mut hasher);
+    }
+
+    for id in sorted_ids(self.bullets.keys()) {
+      hasher.write_u32(id);
+      self.bullets[&id].hash_state(&mut hasher);
+    }
+
+    for id in sorted_ids(self.platforms.keys()) {
+      hasher.write_u32(id);
+      self.platforms[&id].hash_state(&mut hasher);
+    }
+
+    for id in sorted_ids(self.crates.keys()) {
+      hasher.write_u32(id);
+      self.crates[&id].hash_state(&mut hasher);
+    }
+
+    for id in sorted_ids(self.sensors.keys()) {
+      hasher.write_u32(id);
+
+      for body_id in sorted_ids(self.sensors[&id].overlaps.iter()) {
+        hasher.write_u32(body_id);
+      }
+    }
+
+    hasher.finish()
+  }
+}
@@ -1,11 +1,22 @@
 mod cells;
 mod body;
 mod engine;
+mod error;
 mod grid;
+mod hash;
+mod raycast;
+mod replay;
+mod snapshot;
 mod world;
 
 pub use crate::{
+  body::{ BodyClass, player::PlayerConfig },
+  cells::SlopeShape,
   engine::Direction,
+  error::PhysError,
+  raycast::RaycastHit,
+  replay::Replay,
+  snapshot::Snapshot,
   world::World
 };
 
@@ -0,0 +1,296 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use js_sys::Int32Array;
+
+use std::collections::HashSet;
+use crate::body::{ BodyId, BodyClass };
+use crate::engine::{ BLOCK_SIZE, Bounds };
+use crate::grid::get_region_by_point;
+use crate::world::World;
+
+/**
+ * Результат пересечения луча
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RaycastHit {
+  // Класс тела, для Block тел - Fixed
+  pub class: BodyClass,
+  // Идентификатор тела (для Block тел - идентификатор ячейки)
+  pub id: BodyId,
+  // Точка пересечения
+  pub x: i32,
+  pub y: i32,
+  // Нормаль стороны, в которую попал луч
+  pub normal_x: i32,
+  pub normal_y: i32,
+  // Расстояние от начала луча до точки пересечения
+  pub distance: i32
+}
+
+/**
+ * Пересечение луча с телом, найденное при обходе
+ */
+struct BodyHit {
+  id: BodyId,
+  class: BodyClass,
+  distance: f64,
+  normal_x: i32,
+  normal_y: i32
+}
+
+/**
+ * Пересечение луча с ограничительным прямоугольником методом
+ * отсечения по осям, возвращает расстояние и нормаль стороны входа.
+ * Прямоугольники, содержащие начало луча, пропускаются
+ *
+ * Используются только базовые операции f64 (сложение, вычитание,
+ * умножение и деление), результат которых одинаков на всех платформах
+ */
+fn intersect_bounds(
+  origin_x: f64, origin_y: f64, direction_x: f64, direction_y: f64,
+  bounds: &Bounds
+) -> Option<(f64, i32, i32)> {
+  let mut t_min = f64::NEG_INFINITY;
+  let mut t_max = f64::INFINITY;
+  let mut normal_x = 0;
+  let mut normal_y = 0;
+
+  let axes = [
+    (origin_x, direction_x, bounds.min_x as f64, bounds.max_x as f64, true),
+    (origin_y, direction_y, bounds.min_y as f64, bounds.max_y as f64, false)
+  ];
+
+  for (origin, direction, min, max, is_x) in axes {
+    if direction == 0.0 {
+      if origin <= min || origin >= max {
+        return None
+      }
+
+      continue
+    }
+
+    let t1 = (min - origin) / direction;
+    let t2 = (max - origin) / direction;
+    let (t_near, t_far, normal) = if t1 < t2 { (t1, t2, -1) } else { (t2, t1, 1) };
+
+    if t_near > t_min {
+      t_min = t_near;
+
+      if is_x {
+        normal_x = normal;
+        normal_y = 0;
+      } else {
+        normal_x = 0;
+        normal_y = normal;
+      }
+    }
+
+    if t_far < t_max {
+      t_max = t_far;
+    }
+  }
+
+  if t_min > t_max || t_min <= 0.0 {
+    return None
+  }
+
+  Some((t_min, normal_x, normal_y))
+}
+
+impl World {
+  /**
+   * Поиск первого пересечения луча из точки (origin_x, origin_y)
+   * в направлении (direction_x, direction_y) на расстоянии
+   * не более max_distance
+   *
+   * mask - битовая маска классов тел, с которыми проверяется
+   * пересечение (бит 1 << BodyClass), бит Fixed включает Block тела.
+   * Луч проходит по ячейкам Cells и проверяет тела регионов Grid,
+   * через которые проходит. Тела и ячейка, в которых находится
+   * начало луча, не учитываются
+   */
+  fn _raycast(
+    &self, origin_x: i32, origin_y: i32,
+    direction_x: i32, direction_y: i32,
+    max_distance: i32, mask: u32
+  ) -> Option<RaycastHit> {
+    // Квадраты вычисляются умножением: точность powi на разных
+    // платформах не гарантируется, а sqrt, как и базовые операции,
+    // по IEEE 754 округляется корректно
+    let direction_x_f = direction_x as f64;
+    let direction_y_f = direction_y as f64;
+    let length = (
+      direction_x_f * direction_x_f + direction_y_f * direction_y_f
+    ).sqrt();
+
+    if length == 0.0 || max_distance < 0 {
+      return None
+    }
+
+    let ox = origin_x as f64;
+    let oy = origin_y as f64;
+    let dx = direction_x as f64 / length;
+    let dy = direction_y as f64 / length;
+    let max_distance = max_distance as f64;
+    let check_blocks = mask & (1 << BodyClass::Fixed as u32) != 0;
+
+    let mut cell_x = origin_x >> 7;
+    let mut cell_y = origin_y >> 7;
+    let step_x = direction_x.signum();
+    let step_y = direction_y.signum();
+
+    let block_size = BLOCK_SIZE as f64;
+    let t_delta_x = if dx == 0.0 { f64::INFINITY } else { block_size / dx.abs() };
+    let t_delta_y = if dy == 0.0 { f64::INFINITY } else { block_size / dy.abs() };
+    let mut t_max_x = match step_x {
+      1 => ((cell_x + 1) as f64 * block_size - ox) / dx,
+      -1 => (cell_x as f64 * block_size - ox) / dx,
+      _ => f64::INFINITY
+    };
+    let mut t_max_y = match step_y {
+      1 => ((cell_y + 1) as f64 * block_size - oy) / dy,
+      -1 => (cell_y as f64 * block_size - oy) / dy,
+      _ => f64::INFINITY
+    };
+
+    let mut t = 0.0;
+    let mut normal_x = 0;
+    let mut normal_y = 0;
+    let mut checked_regions = HashSet::new();
+    let mut body_hit: Option<BodyHit> = None;
+    let mut block_hit: Option<(BodyId, f64, i32, i32)> = None;
+
+    while t <= max_distance && self.cells.contains(cell_x, cell_y) {
+      let region = get_region_by_point(cell_x * BLOCK_SIZE, cell_y * BLOCK_SIZE);
+
+      if checked_regions.insert(region) {
+        for id in self.grid.get_region_bodies(region).into_iter().flatten() {
+          let rect = match self.rects.get(id) {
+            Some(rect) => rect,
+            None => continue
+          };
+
+          if mask & (1 << rect.class as u32) == 0 {
+            continue
+          }
+
+          let (distance, hit_normal_x, hit_normal_y) = match intersect_bounds(
+            ox, oy, dx, dy, &rect.bounds
+          ) {
+            Some(hit) => hit,
+            None => continue
+          };
+
+          let is_closer = match &body_hit {
+            Some(hit) => distance < hit.distance
+              || (distance == hit.distance && *id < hit.id),
+            None => true
+          };
+
+          if is_closer {
+            body_hit = Some(BodyHit {
+              id: *id,
+              class: rect.class,
+              distance,
+              normal_x: hit_normal_x,
+              normal_y: hit_normal_y
+            });
+          }
+        }
+      }
+
+      // Тела из непроверенных регионов пересекаются дальше
+      // начала текущей ячейки, поэтому найденное тело - первое
+      if matches!(&body_hit, Some(hit) if hit.distance <= t) {
+        break
+      }
+
+      if check_blocks && t > 0.0 && self.cells.is_block(cell_x, cell_y) {
+        let id = self.cells.get_cell_id(cell_x, cell_y);
+        block_hit = Some((id, t, normal_x, normal_y));
+        break
+      }
+
+      if t_max_x < t_max_y {
+        cell_x += step_x;
+        t = t_max_x;
+        t_max_x += t_delta_x;
+        normal_x = -step_x;
+        normal_y = 0;
+      } else {
+        cell_y += step_y;
+        t = t_max_y;
+        t_max_y += t_delta_y;
+        normal_x = 0;
+        normal_y = -step_y;
+      }
+    }
+
+    let (class, id, distance, normal_x, normal_y) = match (body_hit, block_hit) {
+      (Some(body), Some((_, block_distance, _, _))) if body.distance <= block_distance => {
+        (body.class, body.id, body.distance, body.normal_x, body.normal_y)
+      },
+      (_, Some((id, distance, normal_x, normal_y))) => {
+        (BodyClass::Fixed, id, distance, normal_x, normal_y)
+      },
+      (Some(body), None) => {
+        (body.class, body.id, body.distance, body.normal_x, body.normal_y)
+      },
+      (None, None) => return None
+    };
+
+    if distance > max_distance {
+      return None
+    }
+
+    Some(RaycastHit {
+      class,
+      id,
+      x: (ox + dx * distance).floor() as i32,
+      y: (oy + dy * distance).floor() as i32,
+      normal_x,
+      normal_y,
+      distance: distance as i32
+    })
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn raycast(
+    &self, origin_x: i32, origin_y: i32,
+    direction_x: i32, direction_y: i32,
+    max_distance: i32, mask: u32
+  ) -> Option<RaycastHit> {
+    self._raycast(
+      origin_x, origin_y, direction_x, direction_y, max_distance, mask
+    )
+  }
+
+  /**
+   * Для wasm результат упаковывается в массив
+   * [класс, идентификатор, x, y, нормаль x, нормаль y, расстояние],
+   * при отсутствии пересечения возвращается пустой массив
+   */
+  #[cfg(target_arch = "wasm32")]
+  pub fn raycast(
+    &self, origin_x: i32, origin_y: i32,
+    direction_x: i32, direction_y: i32,
+    max_distance: i32, mask: u32
+  ) -> Int32Array {
+    let hit = match self._raycast(
+      origin_x, origin_y, direction_x, direction_y, max_distance, mask
+    ) {
+      Some(hit) => hit,
+      None => return Int32Array::new_with_length(0)
+    };
+
+    Int32Array::from(&[
+      hit.class as i32, hit.id as i32, hit.x, hit.y,
+      hit.normal_x, hit.normal_y, hit.distance
+    ][..])
+  }
+}
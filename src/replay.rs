@@ -0,0 +1,579 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use js_sys::Int32Array;
+
+use crate::body::{ BodyId, player::PlayerConfig };
+use crate::cells::SlopeShape;
+use crate::engine::{ Direction, UpdateResults };
+use crate::world::World;
+
+/**
+ * Версия формата записи, первый элемент записанного массива
+ *
+ * Версия 1 - текущий формат: заголовок, затем записи переменной
+ * длины (номер шага, тип ввода и аргументы ввода). Версию
+ * необходимо увеличивать при каждом изменении общей структуры
+ * записи или упаковки существующих вводов (количества или порядка
+ * аргументов), иначе записи старых версий распакуются со смещением
+ * аргументов, вместо того чтобы быть отклоненными. Добавление
+ * нового типа ввода старые записи не затрагивает
+ */
+const REPLAY_VERSION: i32 = 1;
+/**
+ * Длина заголовка записи: версия, ширина и высота мира в блоках
+ */
+const REPLAY_HEADER_SIZE: usize = 3;
+/**
+ * Максимальное количество ячеек мира в записи, заголовок
+ * поврежденной записи не должен приводить к выделению
+ * памяти под огромный мир
+ */
+const REPLAY_MAX_CELLS: i64 = 1 << 24;
+
+/**
+ * Ввод, изменяющий состояние мира
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Input {
+  // Шаг симуляции длительностью delta микросекунд
+  Step { delta: i32 },
+  PlayerCreate { x: i32, y: i32 },
+  PlayerRun { id: BodyId, direction: Direction },
+  PlayerJump { id: BodyId },
+  BlockCreate { x: i32, y: i32 },
+  BlockRemove { id: BodyId },
+  ItemCreate { x: i32, y: i32 },
+  Remove { id: BodyId },
+  SensorCreate { x: i32, y: i32, width: i32, height: i32 },
+  BulletCreate { x: i32, y: i32, vx: i32, vy: i32, owner: BodyId },
+  BulletSetGravity { id: BodyId, gravity: i32 },
+  PlatformCreate { x: i32, y: i32, width: i32, height: i32 },
+  PlatformSetVelocity { id: BodyId, vx: i32, vy: i32 },
+  PlatformAddWaypoint { id: BodyId, x: i32, y: i32 },
+  PlatformSetSpeed { id: BodyId, speed: i32 },
+  OneWayCreate { x: i32, y: i32 },
+  OneWayRemove { id: BodyId },
+  PlayerDrop { id: BodyId },
+  LadderCreate { x: i32, y: i32 },
+  LadderRemove { id: BodyId },
+  PlayerClimb { id: BodyId, direction: i32 },
+  SlopeCreate { x: i32, y: i32, shape: SlopeShape },
+  SlopeRemove { id: BodyId },
+  LiquidCreate { x: i32, y: i32 },
+  LiquidRemove { id: BodyId },
+  CrateCreate { x: i32, y: i32, width: i32, height: i32 },
+  PlayerSetConfig { id: BodyId, config: PlayerConfig },
+  SetPlayersConfig { config: PlayerConfig },
+  PlayerSetSize { id: BodyId, width: i32, height: i32 },
+  PlayerCrouch { id: BodyId, is_crouch: bool },
+  PlayerJumpRelease { id: BodyId }
+}
+
+impl Input {
+  /**
+   * Упаковка ввода: тип ввода и его аргументы
+   */
+  fn encode(&self, result: &mut Vec<i32>) {
+    match *self {
+      Input::Step { delta } => result.extend_from_slice(&[0, delta]),
+      Input::PlayerCreate { x, y } => result.extend_from_slice(&[1, x, y]),
+      Input::PlayerRun { id, direction } => {
+        result.extend_from_slice(&[2, id as i32, direction as i32])
+      },
+      Input::PlayerJump { id } => result.extend_from_slice(&[3, id as i32]),
+      Input::BlockCreate { x, y } => result.extend_from_slice(&[4, x, y]),
+      Input::BlockRemove { id } => result.extend_from_slice(&[5, id as i32]),
+      Input::ItemCreate { x, y } => result.extend_from_slice(&[6, x, y]),
+      Input::Remove { id } => result.extend_from_slice(&[7, id as i32]),
+      Input::SensorCreate { x, y, width, height } => {
+        result.extend_from_slice(&[8, x, y, width, height])
+      },
+      Input::BulletCreate { x, y, vx, vy, owner } => {
+        result.extend_from_slice(&[9, x, y, vx, vy, owner as i32])
+      },
+      Input::BulletSetGravity { id, gravity } => {
+        result.extend_from_slice(&[10, id as i32, gravity])
+      },
+      Input::PlatformCreate { x, y, width, height } => {
+        result.extend_from_slice(&[11, x, y, width, height])
+      },
+      Input::PlatformSetVelocity { id, vx, vy } => {
+        result.extend_from_slice(&[12, id as i32, vx, vy])
+      },
+      Input::PlatformAddWaypoint { id, x, y } => {
+        result.extend_from_slice(&[13, id as i32, x, y])
+      },
+      Input::PlatformSetSpeed { id, speed } => {
+        result.extend_from_slice(&[14, id as i32, speed])
+      },
+      Input::OneWayCreate { x, y } => result.extend_from_slice(&[15, x, y]),
+      Input::OneWayRemove { id } => result.extend_from_slice(&[16, id as i32]),
+      Input::PlayerDrop { id } => result.extend_from_slice(&[17, id as i32]),
+      Input::LadderCreate { x, y } => result.extend_from_slice(&[18, x, y]),
+      Input::LadderRemove { id } => result.extend_from_slice(&[19, id as i32]),
+      Input::PlayerClimb { id, direction } => {
+        result.extend_from_slice(&[20, id as i32, direction])
+      },
+      Input::SlopeCreate { x, y, shape } => {
+        result.extend_from_slice(&[21, x, y, shape as i32])
+      },
+      Input::SlopeRemove { id } => result.extend_from_slice(&[22, id as i32]),
+      Input::LiquidCreate { x, y } => result.extend_from_slice(&[23, x, y]),
+      Input::LiquidRemove { id } => result.extend_from_slice(&[24, id as i32]),
+      Input::CrateCreate { x, y, width, height } => {
+        result.extend_from_slice(&[25, x, y, width, height])
+      },
+      Input::PlayerSetConfig { id, config } => {
+        result.extend_from_slice(&[26, id as i32]);
+        config.encode(result);
+      },
+      Input::SetPlayersConfig { config } => {
+        result.push(27);
+        config.encode(result);
+      },
+      Input::PlayerSetSize { id, width, height } => {
+        result.extend_from_slice(&[28, id as i32, width, height])
+      },
+      Input::PlayerCrouch { id, is_crouch } => {
+        result.extend_from_slice(&[29, id as i32, is_crouch as i32])
+      },
+      Input::PlayerJumpRelease { id } => {
+        result.extend_from_slice(&[30, id as i32])
+      }
+    }
+  }
+
+  /**
+   * Количество аргументов ввода по его типу
+   */
+  fn arity(class: i32) -> Option<usize> {
+    let arity = match class {
+      0 | 3 | 5 | 7 | 16 | 17 | 19 | 22 | 24 | 30 => 1,
+      1 | 2 | 4 | 6 | 10 | 14 | 15 | 18 | 20 | 23 | 29 => 2,
+      12 | 13 | 21 | 28 => 3,
+      8 | 11 | 25 => 4,
+      9 => 5,
+      27 => 6,
+      26 => 7,
+      _ => return None
+    };
+
+    Some(arity)
+  }
+
+  /**
+   * Распаковка ввода по типу и аргументам
+   */
+  fn decode(class: i32, args: &[i32]) -> Option<Self> {
+    let input = match class {
+      // Шаг с отрицательной длительностью при записи
+      // невозможен (см. World::_step), запись повреждена
+      0 if args[0] >= 0 => Input::Step { delta: args[0] },
+      1 => Input::PlayerCreate { x: args[0], y: args[1] },
+      2 => Input::PlayerRun {
+        id: args[0] as BodyId,
+        direction: match args[1] {
+          0 => Direction::None,
+          1 => Direction::Left,
+          2 => Direction::Right,
+          _ => return None
+        }
+      },
+      3 => Input::PlayerJump { id: args[0] as BodyId },
+      4 => Input::BlockCreate { x: args[0], y: args[1] },
+      5 => Input::BlockRemove { id: args[0] as BodyId },
+      6 => Input::ItemCreate { x: args[0], y: args[1] },
+      7 => Input::Remove { id: args[0] as BodyId },
+      8 => Input::SensorCreate {
+        x: args[0], y: args[1], width: args[2], height: args[3]
+      },
+      9 => Input::BulletCreate {
+        x: args[0], y: args[1], vx: args[2], vy: args[3],
+        owner: args[4] as BodyId
+      },
+      10 => Input::BulletSetGravity {
+        id: args[0] as BodyId, gravity: args[1]
+      },
+      11 => Input::PlatformCreate {
+        x: args[0], y: args[1], width: args[2], height: args[3]
+      },
+      12 => Input::PlatformSetVelocity {
+        id: args[0] as BodyId, vx: args[1], vy: args[2]
+      },
+      13 => Input::PlatformAddWaypoint {
+        id: args[0] as BodyId, x: args[1], y: args[2]
+      },
+      14 => Input::PlatformSetSpeed { id: args[0] as BodyId, speed: args[1] },
+      15 => Input::OneWayCreate { x: args[0], y: args[1] },
+      16 => Input::OneWayRemove { id: args[0] as BodyId },
+      17 => Input::PlayerDrop { id: args[0] as BodyId },
+      18 => Input::LadderCreate { x: args[0], y: args[1] },
+      19 => Input::LadderRemove { id: args[0] as BodyId },
+      20 => Input::PlayerClimb { id: args[0] as BodyId, direction: args[1] },
+      21 => Input::SlopeCreate {
+        x: args[0], y: args[1], shape: SlopeShape::from_u8(args[2] as u8)?
+      },
+      22 => Input::SlopeRemove { id: args[0] as BodyId },
+      23 => Input::LiquidCreate { x: args[0], y: args[1] },
+      24 => Input::LiquidRemove { id: args[0] as BodyId },
+      25 => Input::CrateCreate {
+        x: args[0], y: args[1], width: args[2], height: args[3]
+      },
+      26 => Input::PlayerSetConfig {
+        id: args[0] as BodyId, config: PlayerConfig::decode(&args[1..])
+      },
+      27 => Input::SetPlayersConfig { config: PlayerConfig::decode(args) },
+      28 => Input::PlayerSetSize {
+        id: args[0] as BodyId, width: args[1], height: args[2]
+      },
+      29 => Input::PlayerCrouch { id: args[0] as BodyId, is_crouch: args[1] != 0 },
+      30 => Input::PlayerJumpRelease { id: args[0] as BodyId },
+      _ => return None
+    };
+
+    Some(input)
+  }
+}
+
+/**
+ * Ввод вместе с номером шага, перед которым он был применен
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct InputRecord {
+  pub tick: u32,
+  pub input: Input
+}
+
+/**
+ * Запись ввода мира
+ */
+#[derive(Default, Clone, Debug)]
+pub struct Recording {
+  pub width_blocks: i32,
+  pub height_blocks: i32,
+  pub records: Vec<InputRecord>
+}
+
+impl Recording {
+  /**
+   * Упаковка записи в плоский массив:
+   * заголовок (версия, ширина, высота), затем для каждого ввода
+   * номер шага, тип ввода и его аргументы (количество аргументов
+   * определяется типом ввода)
+   */
+  pub fn to_vec(&self) -> Vec<i32> {
+    let mut result = Vec::with_capacity(
+      REPLAY_HEADER_SIZE + self.records.len() * 4
+    );
+
+    result.push(REPLAY_VERSION);
+    result.push(self.width_blocks);
+    result.push(self.height_blocks);
+
+    for record in &self.records {
+      result.push(record.tick as i32);
+      record.input.encode(&mut result);
+    }
+
+    result
+  }
+
+  /**
+   * Распаковка записи из плоского массива,
+   * возвращает None для поврежденных данных
+   */
+  pub fn from_slice(data: &[i32]) -> Option<Self> {
+    if data.len() < REPLAY_HEADER_SIZE || data[0] != REPLAY_VERSION {
+      return None
+    }
+
+    let (width_blocks, height_blocks) = (data[1], data[2]);
+
+    if width_blocks <= 0 || height_blocks <= 0
+    || width_blocks as i64 * height_blocks as i64 > REPLAY_MAX_CELLS {
+      return None
+    }
+
+    let mut records = Vec::new();
+    let mut position = REPLAY_HEADER_SIZE;
+
+    while position < data.len() {
+      let tick = data[position] as u32;
+      let class = *data.get(position + 1)?;
+      let arity = Input::arity(class)?;
+      let args = data.get((position + 2)..(position + 2 + arity))?;
+
+      records.push(InputRecord {
+        tick,
+        input: Input::decode(class, args)?
+      });
+
+      position += 2 + arity;
+    }
+
+    Some(Self {
+      width_blocks,
+      height_blocks,
+      records
+    })
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Начинает запись ввода
+   *
+   * Воспроизведение начинается с нового пустого мира, поэтому
+   * для точного повтора запись нужно начинать сразу после
+   * создания мира, до первого ввода. Восстановление мира из
+   * снимка (restore) прекращает и отбрасывает запись
+   */
+  pub fn record_start(&mut self) {
+    self.recording = Some(Recording {
+      width_blocks: self.cells.width,
+      height_blocks: self.cells.height,
+      records: Vec::new()
+    });
+  }
+
+  pub fn is_recording(&self) -> bool {
+    self.recording.is_some()
+  }
+
+  /**
+   * Останавливает запись ввода и возвращает упакованную запись
+   */
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn record_stop(&mut self) -> Vec<i32> {
+    match self.recording.take() {
+      Some(recording) => recording.to_vec(),
+      None => Vec::new()
+    }
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  pub fn record_stop(&mut self) -> Int32Array {
+    match self.recording.take() {
+      Some(recording) => Int32Array::from(&recording.to_vec()[..]),
+      None => Int32Array::new_with_length(0)
+    }
+  }
+}
+
+impl World {
+  /**
+   * Добавляет ввод в запись, если она ведется
+   */
+  pub(crate) fn record(&mut self, input: Input) {
+    if let Some(recording) = &mut self.recording {
+      recording.records.push(InputRecord {
+        tick: self.tick,
+        input
+      });
+    }
+  }
+}
+
+/**
+ * Воспроизведение записи ввода на новом мире
+ */
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct Replay {
+  world: World,
+  records: Vec<InputRecord>,
+  position: usize
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Replay {
+  /**
+   * Создает воспроизведение из упакованной записи,
+   * возвращает None для поврежденных данных
+   */
+  pub fn new(data: &[i32]) -> Option<Replay> {
+    let recording = Recording::from_slice(data)?;
+
+    Some(Self {
+      world: World::new(recording.width_blocks, recording.height_blocks),
+      records: recording.records,
+      position: 0
+    })
+  }
+
+  pub fn is_finished(&self) -> bool {
+    self.position >= self.records.len()
+  }
+
+  /**
+   * Применяет записанный ввод вплоть до следующего шага
+   * симуляции включительно и возвращает результаты этого шага
+   */
+  fn _step(&mut self) -> UpdateResults {
+    while let Some(record) = self.records.get(self.position) {
+      self.position += 1;
+
+      let world = &mut self.world;
+
+      match record.input {
+        Input::Step { delta } => return world.simulate(delta),
+        Input::PlayerCreate { x, y } => { world.player_create(x, y); },
+        Input::PlayerRun { id, direction } => world.player_run(id, direction),
+        Input::PlayerJump { id } => world.player_jump(id),
+        Input::BlockCreate { x, y } => { world.block_create(x, y); },
+        Input::BlockRemove { id } => world.block_remove(id),
+        Input::ItemCreate { x, y } => { world.item_create(x, y); },
+        Input::Remove { id } => world.remove(id),
+        Input::SensorCreate { x, y, width, height } => {
+          world.sensor_create(x, y, width, height);
+        },
+        Input::BulletCreate { x, y, vx, vy, owner } => {
+          world.bullet_create(x, y, vx, vy, owner);
+        },
+        Input::BulletSetGravity { id, gravity } => {
+          world.bullet_set_gravity(id, gravity)
+        },
+        Input::PlatformCreate { x, y, width, height } => {
+          world.platform_create(x, y, width, height);
+        },
+        Input::PlatformSetVelocity { id, vx, vy } => {
+          world.platform_set_velocity(id, vx, vy)
+        },
+        Input::PlatformAddWaypoint { id, x, y } => {
+          world.platform_add_waypoint(id, x, y)
+        },
+        Input::PlatformSetSpeed { id, speed } => {
+          world.platform_set_speed(id, speed)
+        },
+        Input::OneWayCreate { x, y } => { world.one_way_create(x, y); },
+        Input::OneWayRemove { id } => world.one_way_remove(id),
+        Input::PlayerDrop { id } => world.player_drop(id),
+        Input::LadderCreate { x, y } => { world.ladder_create(x, y); },
+        Input::LadderRemove { id } => world.ladder_remove(id),
+        Input::PlayerClimb { id, direction } => world.player_climb(id, direction),
+        Input::SlopeCreate { x, y, shape } => { world.slope_create(x, y, shape); },
+        Input::SlopeRemove { id } => world.slope_remove(id),
+        Input::LiquidCreate { x, y } => { world.liquid_create(x, y); },
+        Input::LiquidRemove { id } => world.liquid_remove(id),
+        Input::CrateCreate { x, y, width, height } => {
+          world.crate_create(x, y, width, height);
+        },
+        Input::PlayerSetConfig { id, config } => {
+          world.player_set_config(id, &config)
+        },
+        Input::SetPlayersConfig { config } => world.set_players_config(&config),
+        Input::PlayerSetSize { id, width, height } => {
+          world.player_set_size(id, width, height)
+        },
+        Input::PlayerCrouch { id, is_crouch } => world.player_crouch(id, is_crouch),
+        Input::PlayerJumpRelease { id } => world.player_jump_release(id)
+      }
+    }
+
+    UpdateResults::default()
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn step(&mut self) -> UpdateResults {
+    self._step()
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  pub fn step(&mut self) -> Int32Array {
+    crate::world::update_results_to_array(&self._step())
+  }
+
+  /**
+   * Номер текущего шага воспроизводимого мира
+   */
+  pub fn tick(&self) -> u32 {
+    self.world.tick()
+  }
+
+  /**
+   * Контрольная сумма состояния воспроизводимого мира
+   */
+  pub fn state_hash(&self) -> u64 {
+    self.world.state_hash()
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Replay {
+  pub fn world(&self) -> &World {
+    &self.world
+  }
+
+  pub fn into_world(self) -> World {
+    self.world
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn replay_reaches_same_state() {
+    let mut world = World::new(60, 60);
+    world.record_start();
+
+    let mut blocks = Vec::new();
+    for x in 0..60 {
+      blocks.push(world.block_create(x, 30));
+    }
+
+    let player_id = world.player_create(960, 3840);
+    world.item_create(2000, 3000);
+    world.crate_create(3000, 3840, 100, 100);
+    world.sensor_create(2500, 3840, 256, 256);
+
+    for tick in 0..120 {
+      match tick {
+        10 => world.player_run(player_id, Direction::Right),
+        30 => world.player_jump(player_id),
+        60 => world.block_remove(blocks[20]),
+        90 => world.player_run(player_id, Direction::Left),
+        _ => {}
+      }
+
+      world.step(1.0 / 60.0);
+    }
+
+    let data = world.record_stop();
+    let mut replay = Replay::new(&data).unwrap();
+
+    while !replay.is_finished() {
+      replay.step();
+    }
+
+    assert_eq!(replay.tick(), world.tick());
+    assert_eq!(replay.state_hash(), world.state_hash());
+  }
+
+  #[test]
+  fn from_slice_rejects_corrupted_data() {
+    let step = [0, 0, 16666];
+
+    let with_header = |header: [i32; 3]| {
+      let mut data = header.to_vec();
+      data.extend_from_slice(&step);
+      data
+    };
+
+    assert!(Recording::from_slice(&with_header([REPLAY_VERSION, 60, 60])).is_some());
+
+    assert!(Recording::from_slice(&with_header([REPLAY_VERSION + 1, 60, 60])).is_none());
+    assert!(Recording::from_slice(&with_header([REPLAY_VERSION, -5, 60])).is_none());
+    assert!(Recording::from_slice(&with_header([REPLAY_VERSION, 60, 0])).is_none());
+    assert!(
+      Recording::from_slice(&with_header([REPLAY_VERSION, i32::MAX, i32::MAX])).is_none()
+    );
+    assert!(Recording::from_slice(&[REPLAY_VERSION, 60]).is_none());
+
+    assert!(Recording::from_slice(&[REPLAY_VERSION, 60, 60, 0, 0, -1]).is_none());
+    assert!(Recording::from_slice(&[REPLAY_VERSION, 60, 60, 0, 0]).is_none());
+    assert!(Recording::from_slice(&[REPLAY_VERSION, 60, 60, 0, 999, 0]).is_none());
+  }
+}
@@ -0,0 +1,166 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::body::{
+  BodiesIds, Bodies,
+  bullet::BodyBullet, item::BodyItem, player::BodyPlayer,
+  platform::BodyPlatform, crates::BodyCrate, sensor::BodySensor
+};
+use crate::cells::Cells;
+use crate::engine::Rects;
+use crate::grid::Grid;
+use crate::world::World;
+
+/**
+ * Снимок состояния физического мира
+ *
+ * Содержит копию всех данных, от которых зависит результат
+ * следующих шагов симуляции, и позволяет вернуть мир к прошлому
+ * шагу для повторной симуляции (rollback).
+ * Настройки мира (размер шага и т.п.) в снимок не входят.
+ */
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+  tick: u32,
+  accumulator: i32,
+  alpha: f32,
+  next_body_id: u32,
+  cells: Cells,
+  grid: Grid,
+  ids: BodiesIds,
+  rects: Rects,
+  items: Bodies<BodyItem>,
+  players: Bodies<BodyPlayer>,
+  sensors: Bodies<BodySensor>,
+  bullets: Bodies<BodyBullet>,
+  platforms: Bodies<BodyPlatform>,
+  crates: Bodies<BodyCrate>,
+  ids_to_remove: BodiesIds
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Snapshot {
+  /**
+   * Номер шага, на котором был сделан снимок
+   */
+  pub fn tick(&self) -> u32 {
+    self.tick
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl World {
+  /**
+   * Создает снимок текущего состояния мира
+   */
+  pub fn snapshot(&self) -> Snapshot {
+    Snapshot {
+      tick: self.tick,
+      accumulator: self.accumulator,
+      alpha: self.alpha,
+      next_body_id: self.next_body_id,
+      cells: self.cells.clone(),
+      grid: self.grid.clone(),
+      ids: self.ids.clone(),
+      rects: self.rects.clone(),
+      items: self.items.clone(),
+      players: self.players.clone(),
+      sensors: self.sensors.clone(),
+      bullets: self.bullets.clone(),
+      platforms: self.platforms.clone(),
+      crates: self.crates.clone(),
+      ids_to_remove: self.ids_to_remove.clone()
+    }
+  }
+
+  /**
+   * Восстанавливает состояние мира из снимка
+   *
+   * Откат в запись ввода не попадает, поэтому ведущаяся запись
+   * прекращается и отбрасывается: воспроизведение такой записи
+   * разошлось бы с миром
+   */
+  pub fn restore(&mut self, snapshot: &Snapshot) {
+    self.recording = None;
+
+    self.tick = snapshot.tick;
+    self.accumulator = snapshot.accumulator;
+    self.alpha = snapshot.alpha;
+    self.next_body_id = snapshot.next_body_id;
+    self.cells = snapshot.cells.clone();
+    self.grid = snapshot.grid.clone();
+    self.ids = snapshot.ids.clone();
+    self.rects = snapshot.rects.clone();
+    self.items = snapshot.items.clone();
+    self.players = snapshot.players.clone();
+    self.sensors = snapshot.sensors.clone();
+    self.bullets = snapshot.bullets.clone();
+    self.platforms = snapshot.platforms.clone();
+    self.crates = snapshot.crates.clone();
+    self.ids_to_remove = snapshot.ids_to_remove.clone();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::engine::Direction;
+  use crate::world::World;
+
+  fn create_world() -> World {
+    let mut world = World::new(60, 60);
+
+    for x in 0..60 {
+      world.block_create(x, 30);
+    }
+
+    let player_id = world.player_create(960, 3840);
+    world.player_run(player_id, Direction::Right);
+    world.item_create(2000, 3000);
+    world.crate_create(3000, 3840, 100, 100);
+
+    world
+  }
+
+  #[test]
+  fn restore_returns_same_state() {
+    let mut world = create_world();
+
+    for _ in 0..30 {
+      world.step(1.0 / 60.0);
+    }
+
+    let snapshot = world.snapshot();
+    let snapshot_hash = world.state_hash();
+
+    for _ in 0..60 {
+      world.step(1.0 / 60.0);
+    }
+
+    let future_hash = world.state_hash();
+    assert_ne!(future_hash, snapshot_hash);
+
+    world.restore(&snapshot);
+    assert_eq!(world.state_hash(), snapshot_hash);
+    assert_eq!(world.tick(), snapshot.tick());
+
+    for _ in 0..60 {
+      world.step(1.0 / 60.0);
+    }
+
+    assert_eq!(world.state_hash(), future_hash);
+  }
+
+  #[test]
+  fn restore_stops_recording() {
+    let mut world = create_world();
+    let snapshot = world.snapshot();
+
+    world.record_start();
+    world.step(1.0 / 60.0);
+    world.restore(&snapshot);
+
+    assert!(!world.is_recording());
+  }
+}
@@ -8,31 +8,76 @@ use std::time::Instant;
 use instant::Instant;
 
 use crate::body::{
-  BodyId, BodiesIds, BodyClass, Body, Bodies,
-  item::BodyItem, player::BodyPlayer
+  BodyId, BodiesIds, BodyClass, BodyData, Body, Bodies,
+  bullet::BodyBullet, item::BodyItem,
+  player::{
+    BodyPlayer, PlayerConfig, update_climb_players, update_swim_players
+  },
+  platform::{ BodyPlatform, update_carry_players },
+  crates::{ BodyCrate, update_correct_crates, update_push_crates },
+  sensor::{ BodySensor, SensorsOverlaps, update_sensors_overlaps }
 };
 use crate::cells::Cells;
+use crate::error::PhysError;
 use crate::engine::{
-  BLOCK_SIZE, EventClass, Event,
-  PositionUpdate, Rects, UpdateResults,
-  get_bounds_intersection, update_positions_typed, update_correct_players
+  BLOCK_SIZE, EventClass, Event, seconds_to_micros,
+  ObstaclesContacts, PositionUpdate, Rect, Rects, UpdateResults,
+  get_bounds_intersection, update_positions_typed,
+  update_correct_players, update_correct_items, update_correct_bullets
 };
 use crate::grid::Grid;
+use crate::replay::{ Input, Recording };
+
+/**
+ * Шаг фиксированного обновления по умолчанию (в микросекундах)
+ */
+pub const DEFAULT_FIXED_STEP: i32 = 16_667;
+
+/**
+ * Максимальная длительность одного шага симуляции по умолчанию
+ * (в микросекундах), более длинные шаги разбиваются на подшаги,
+ * чтобы тела не проходили сквозь блоки
+ */
+pub const DEFAULT_MAX_STEP: i32 = 33_333;
+
+/**
+ * Максимальное количество подшагов по умолчанию, время сверх
+ * max_step * max_substeps отбрасывается
+ */
+pub const DEFAULT_MAX_SUBSTEPS: u32 = 8;
 
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
   width: i32,
   height: i32,
-  next_body_id: u32,
+  pub(crate) next_body_id: u32,
+  #[cfg_attr(feature = "serde", serde(skip))]
   last_update: Option<Instant>,
+  pub(crate) tick: u32,
+  fixed_step: i32,
+  pub(crate) accumulator: i32,
+  pub(crate) alpha: f32,
+  time_scale: f32,
+  is_paused: bool,
+  max_step: i32,
+  max_substeps: u32,
+  // Параметры движения создаваемых игроков
+  pub(crate) players_config: PlayerConfig,
   pub cells: Cells,
   pub grid: Grid,
   pub ids: BodiesIds,
   pub rects: Rects,
   pub items: Bodies<BodyItem>,
   pub players: Bodies<BodyPlayer>,
-  ids_to_remove: BodiesIds
+  pub sensors: Bodies<BodySensor>,
+  pub bullets: Bodies<BodyBullet>,
+  pub platforms: Bodies<BodyPlatform>,
+  pub crates: Bodies<BodyCrate>,
+  pub(crate) ids_to_remove: BodiesIds,
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub(crate) recording: Option<Recording>
 }
 
 /**
@@ -43,11 +88,23 @@ pub struct World {
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
   width: i32,
   height: i32,
-  next_body_id: u32,
+  pub(crate) next_body_id: u32,
+  #[cfg_attr(feature = "serde", serde(skip))]
   last_update: Option<Instant>,
+  pub(crate) tick: u32,
+  fixed_step: i32,
+  pub(crate) accumulator: i32,
+  pub(crate) alpha: f32,
+  time_scale: f32,
+  is_paused: bool,
+  max_step: i32,
+  max_substeps: u32,
+  // Параметры движения создаваемых игроков
+  pub(crate) players_config: PlayerConfig,
   #[wasm_bindgen(skip)]
   pub cells: Cells,
   #[wasm_bindgen(skip)]
@@ -60,7 +117,17 @@ pub struct World {
   pub items: Bodies<BodyItem>,
   #[wasm_bindgen(skip)]
   pub players: Bodies<BodyPlayer>,
-  ids_to_remove: BodiesIds
+  #[wasm_bindgen(skip)]
+  pub sensors: Bodies<BodySensor>,
+  #[wasm_bindgen(skip)]
+  pub bullets: Bodies<BodyBullet>,
+  #[wasm_bindgen(skip)]
+  pub platforms: Bodies<BodyPlatform>,
+  #[wasm_bindgen(skip)]
+  pub crates: Bodies<BodyCrate>,
+  pub(crate) ids_to_remove: BodiesIds,
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub(crate) recording: Option<Recording>
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
@@ -70,19 +137,33 @@ impl World {
     Self {
       width: width_blocks * BLOCK_SIZE,
       height: height_blocks * BLOCK_SIZE,
+      fixed_step: DEFAULT_FIXED_STEP,
+      time_scale: 1.0,
+      max_step: DEFAULT_MAX_STEP,
+      max_substeps: DEFAULT_MAX_SUBSTEPS,
       cells: Cells::new(width_blocks, height_blocks),
       ..Default::default()
     }
   }
 
   pub(crate) fn next_body_id(&mut self) -> BodyId {
-    self.next_body_id = self.next_body_id + 1;
+    self.next_body_id += 1;
     self.next_body_id
   }
 
+  /**
+   * Добавление прямоугольника создаваемого тела в мир и сетку
+   */
+  pub(crate) fn add_rect(&mut self, rect: Rect) {
+    let id = rect.id;
+
+    self.rects.insert(id, rect);
+    self.grid.add(id, &mut self.rects);
+  }
+
   fn step_clear(&mut self) {
     for id in &self.ids_to_remove {
-      let rect = match self.rects.remove(&id) {
+      let rect = match self.rects.remove(id) {
         Some(rect) => rect,
         None => continue
       };
@@ -90,25 +171,52 @@ impl World {
       match rect.class {
         BodyClass::Player => { self.players.remove(id); },
         BodyClass::Item => { self.items.remove(id); },
-        _ => todo!()
+        BodyClass::Sensor => { self.sensors.remove(id); },
+        BodyClass::Bullet => { self.bullets.remove(id); },
+        BodyClass::Platform => { self.platforms.remove(id); },
+        BodyClass::Crate => { self.crates.remove(id); },
+        _ => ()
       }
 
       self.grid.remove(&rect);
       self.rects.remove(id);
-      self.ids.remove(&id);
+      self.ids.remove(id);
     }
 
     self.ids_to_remove = BodiesIds::default();
   }
 
   fn step_update_positions(
-    &mut self, delta: f32, events: &mut Vec<Event>
+    &mut self, delta: i32, events: &mut Vec<Event>
   ) {
+    update_positions_typed(
+      delta, self.width, self.height,
+      &mut self.rects, &mut self.platforms,
+      &mut self.ids_to_remove, events
+    );
+    update_carry_players(
+      &mut self.rects, &self.platforms, &mut self.players
+    );
     update_positions_typed(
       delta, self.width, self.height,
       &mut self.rects, &mut self.players,
       &mut self.ids_to_remove, events
     );
+    update_positions_typed(
+      delta, self.width, self.height,
+      &mut self.rects, &mut self.items,
+      &mut self.ids_to_remove, events
+    );
+    update_positions_typed(
+      delta, self.width, self.height,
+      &mut self.rects, &mut self.bullets,
+      &mut self.ids_to_remove, events
+    );
+    update_positions_typed(
+      delta, self.width, self.height,
+      &mut self.rects, &mut self.crates,
+      &mut self.ids_to_remove, events
+    );
   }
 
   fn step_broadphase(&mut self) {
@@ -117,10 +225,17 @@ impl World {
     }
   }
 
-  fn step_detect(&mut self, events: &mut Vec<Event>) {
+  fn step_detect(&mut self, events: &mut Vec<Event>) -> ObstaclesContacts {
+    let mut sensors_overlaps = SensorsOverlaps::default();
+    let mut obstacles_contacts = ObstaclesContacts::default();
+
     for pair in self.grid.pairs.values() {
-      let rect1 = self.rects.get(&pair.id1).unwrap();
-      let rect2 = self.rects.get(&pair.id2).unwrap();
+      let (rect1, rect2) = match (
+        self.rects.get(&pair.id1), self.rects.get(&pair.id2)
+      ) {
+        (Some(rect1), Some(rect2)) => (rect1, rect2),
+        _ => continue
+      };
 
       let intersection = get_bounds_intersection(
         &rect1.bounds, &rect2.bounds
@@ -132,55 +247,96 @@ impl World {
 
       match rect1.class {
         BodyClass::Sensor => {
+          sensors_overlaps.entry(rect1.id).or_default().insert(rect2.id);
+          continue
+        },
+        BodyClass::Platform | BodyClass::Crate
+        if rect2.class == BodyClass::Player => {
+          obstacles_contacts.entry(rect2.id).or_default().push(rect1.id);
+          continue
+        },
+        BodyClass::Item if rect2.class == BodyClass::Player => {
           events.push(Event {
-            class: EventClass::Sensor,
+            class: EventClass::Item,
             body_id: rect2.id,
-            trigger_id: rect1.id
+            trigger_id: rect1.id,
+            owner_id: 0,
+            body_data: rect2.data,
+            trigger_data: rect1.data
           });
           continue
         },
-        BodyClass::Item => match rect2.class {
-          BodyClass::Player => {
-            events.push(Event {
-              class: EventClass::Item,
-              body_id: rect2.id,
-              trigger_id: rect1.id
-            });
-            continue
-          }
-          _ => ()
-        },
         _ => ()
       }
 
       match rect2.class {
         BodyClass::Sensor => {
+          sensors_overlaps.entry(rect2.id).or_default().insert(rect1.id);
+          continue
+        },
+        BodyClass::Platform | BodyClass::Crate
+        if rect1.class == BodyClass::Player => {
+          obstacles_contacts.entry(rect1.id).or_default().push(rect2.id);
+          continue
+        },
+        BodyClass::Item if rect1.class == BodyClass::Player => {
           events.push(Event {
-            class: EventClass::Sensor,
+            class: EventClass::Item,
             body_id: rect1.id,
-            trigger_id: rect2.id
+            trigger_id: rect2.id,
+            owner_id: 0,
+            body_data: rect1.data,
+            trigger_data: rect2.data
           });
           continue
         },
-        BodyClass::Item => match rect1.class {
-          BodyClass::Player => {
-            events.push(Event {
-              class: EventClass::Item,
-              body_id: rect1.id,
-              trigger_id: rect2.id
-            });
-            continue
-          }
-          _ => ()
-        },
         _ => ()
       }
     }
+
+    update_sensors_overlaps(
+      &self.rects, &mut self.sensors, sensors_overlaps, events
+    );
+
+    // Порядок пар не определен, а от порядка препятствий зависит
+    // выбор опоры игрока, поэтому препятствия сортируются
+    for obstacles in obstacles_contacts.values_mut() {
+      obstacles.sort_unstable();
+    }
+
+    obstacles_contacts
   }
 
-  fn step_correct(&mut self) {
+  fn step_correct(&mut self, obstacles_contacts: &ObstaclesContacts) {
+    update_correct_crates(
+      &self.cells, &self.grid, &mut self.rects, &mut self.crates
+    );
+    update_push_crates(
+      &self.cells, &self.grid, &mut self.rects, &mut self.crates,
+      &self.players, obstacles_contacts
+    );
     update_correct_players(
-      &self.cells, &mut self.rects, &mut self.players
+      &self.cells, &mut self.rects, &mut self.players,
+      &self.platforms, &self.crates, obstacles_contacts
+    );
+    update_climb_players(
+      &self.cells, &self.rects, &mut self.players
+    );
+    update_correct_items(
+      &self.cells, &mut self.rects, &mut self.items
+    );
+  }
+
+  fn step_swim(&mut self, events: &mut Vec<Event>) {
+    update_swim_players(
+      &self.cells, &self.rects, &mut self.players, events
+    );
+  }
+
+  fn step_correct_bullets(&mut self, events: &mut Vec<Event>) {
+    update_correct_bullets(
+      &self.cells, &self.rects, &mut self.bullets, &self.players,
+      &mut self.ids_to_remove, events
     );
   }
 
@@ -203,17 +359,122 @@ impl World {
       body.prev_x = body.x;
       body.prev_y = body.y;
 
-      body.update_rect(self.rects.get_mut(&id).unwrap());
+      if let Some(rect) = self.rects.get_mut(id) {
+        body.update_rect(rect);
+      }
+    }
+
+    for (id, body) in self.items.iter_mut() {
+      if body.y == body.prev_y {
+        continue
+      }
+
+      positions_updates.push(PositionUpdate {
+        id: *id,
+        x: body.x,
+        y: body.y
+      });
+
+      body.prev_y = body.y;
+    }
+
+    for (id, body) in self.bullets.iter_mut() {
+      if body.x == body.prev_x && body.y == body.prev_y {
+        continue
+      }
+
+      positions_updates.push(PositionUpdate {
+        id: *id,
+        x: body.x,
+        y: body.y
+      });
+
+      body.prev_x = body.x;
+      body.prev_y = body.y;
+
+      if let Some(rect) = self.rects.get_mut(id) {
+        body.update_rect(rect);
+      }
+    }
+
+    for (id, body) in self.platforms.iter_mut() {
+      if body.x == body.prev_x && body.y == body.prev_y {
+        continue
+      }
+
+      positions_updates.push(PositionUpdate {
+        id: *id,
+        x: body.x,
+        y: body.y
+      });
+
+      body.prev_x = body.x;
+      body.prev_y = body.y;
+    }
+
+    for (id, body) in self.crates.iter_mut() {
+      if body.x == body.prev_x && body.y == body.prev_y {
+        continue
+      }
+
+      positions_updates.push(PositionUpdate {
+        id: *id,
+        x: body.x,
+        y: body.y
+      });
+
+      body.prev_x = body.x;
+      body.prev_y = body.y;
     }
 
     positions_updates
   }
 
-  fn _update(&mut self) -> UpdateResults {
-    let delta = match self.last_update {
-      Some(instant) => instant.elapsed().as_secs_f32(),
-      None => 0.0
-    };
+  /**
+   * Выполняет один шаг симуляции длительностью delta микросекунд
+   * с учетом паузы и масштаба времени
+   *
+   * Шаг длиннее max_step разбивается на равные подшаги, каждый
+   * из которых проходит полный цикл обновления с коррекцией позиций,
+   * количество подшагов ограничено max_substeps, время сверх
+   * max_step * max_substeps отбрасывается.
+   * Отрицательная длительность считается нулевой
+   */
+  fn _step(&mut self, delta: i32) -> UpdateResults {
+    if self.is_paused {
+      return UpdateResults::default()
+    }
+
+    let delta = ((delta as f64 * self.time_scale as f64) as i32).max(0);
+
+    if self.max_step <= 0 || delta <= self.max_step {
+      return self.simulate(delta)
+    }
+
+    let delta = (delta as i64)
+      .min(self.max_step as i64 * self.max_substeps as i64) as i32;
+
+    let substeps = (delta + self.max_step - 1) / self.max_step;
+    let substep = delta / substeps;
+    let remainder = delta % substeps;
+
+    let mut update_results = UpdateResults::default();
+
+    for index in 0..substeps {
+      let substep = if index < remainder { substep + 1 } else { substep };
+      update_results.append(self.simulate(substep));
+    }
+
+    update_results
+  }
+
+  /**
+   * Выполняет один шаг симуляции длительностью delta микросекунд
+   * без учета масштаба времени, в запись попадает итоговая
+   * длительность шага
+   */
+  pub(crate) fn simulate(&mut self, delta: i32) -> UpdateResults {
+    self.record(Input::Step { delta });
 
     let mut events: Vec<Event> = Vec::new();
 
@@ -229,18 +490,82 @@ impl World {
 
     self.step_broadphase();
 
-    self.step_detect(&mut events);
+    let obstacles_contacts = self.step_detect(&mut events);
+
+    self.step_correct(&obstacles_contacts);
 
-    self.step_correct();
+    self.step_swim(&mut events);
+
+    self.step_correct_bullets(&mut events);
 
     let positions_updates = self.step_finish();
 
-    self.last_update = Some(Instant::now());
+    self.tick += 1;
 
     UpdateResults {
-      events: events,
-      positions_updates: positions_updates
+      events,
+      positions_updates
+    }
+  }
+
+  /**
+   * Обновление мира на время, прошедшее с предыдущего вызова
+   */
+  fn _update(&mut self) -> UpdateResults {
+    if self.is_paused {
+      self.last_update = None;
+      return UpdateResults::default()
+    }
+
+    let delta = match self.last_update {
+      Some(instant) => {
+        instant.elapsed().as_micros().min(i32::MAX as u128) as i32
+      },
+      None => 0
+    };
+
+    let update_results = self._step(delta);
+
+    self.last_update = Some(Instant::now());
+
+    update_results
+  }
+
+  /**
+   * Накопление реального времени и выполнение необходимого
+   * количества шагов фиксированной длительности.
+   * Остаток накопленного времени (в долях шага) сохраняется
+   * в alpha для интерполяции позиций при отрисовке.
+   * За один вызов выполняется не более max_substeps шагов,
+   * накопленное время сверх них отбрасывается
+   */
+  fn _advance(&mut self, real_dt: f32) -> UpdateResults {
+    let mut update_results = UpdateResults::default();
+
+    if self.fixed_step <= 0 || self.is_paused {
+      return update_results
     }
+
+    self.accumulator = self.accumulator
+      .saturating_add(seconds_to_micros(real_dt).max(0));
+
+    let mut ticks = 0;
+
+    while self.accumulator >= self.fixed_step {
+      if ticks >= self.max_substeps {
+        self.accumulator %= self.fixed_step;
+        break
+      }
+
+      self.accumulator -= self.fixed_step;
+      update_results.append(self._step(self.fixed_step));
+
+      ticks += 1;
+    }
+
+    self.alpha = self.accumulator as f32 / self.fixed_step as f32;
+
+    update_results
   }
 
   #[cfg(not(target_arch = "wasm32"))]
@@ -250,37 +575,204 @@ impl World {
 
   #[cfg(target_arch = "wasm32")]
   pub fn update(&mut self) -> Int32Array {
-    let update_results = self._update();
+    update_results_to_array(&self._update())
+  }
 
-    let mut result = Vec::with_capacity(
-      (update_results.events.len() * 3 +
-       update_results.positions_updates.len() * 3 +
-       3) as usize
-    );
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn step(&mut self, dt: f32) -> UpdateResults {
+    self._step(seconds_to_micros(dt))
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  pub fn step(&mut self, dt: f32) -> Int32Array {
+    update_results_to_array(&self._step(seconds_to_micros(dt)))
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn advance(&mut self, real_dt: f32) -> UpdateResults {
+    self._advance(real_dt)
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  pub fn advance(&mut self, real_dt: f32) -> Int32Array {
+    update_results_to_array(&self._advance(real_dt))
+  }
+
+  /**
+   * Доля шага, оставшаяся в накопителе после последнего advance,
+   * в диапазоне [0, 1)
+   */
+  pub fn alpha(&self) -> f32 {
+    self.alpha
+  }
+
+  /**
+   * Количество выполненных шагов симуляции
+   */
+  pub fn tick(&self) -> u32 {
+    self.tick
+  }
+
+  /**
+   * Установка длительности фиксированного шага (в секундах)
+   */
+  pub fn set_fixed_step(&mut self, fixed_step: f32) {
+    self.fixed_step = seconds_to_micros(fixed_step);
+  }
+
+  /**
+   * Установка масштаба времени: 1 - обычная скорость,
+   * меньше 1 - замедление, больше 1 - ускорение
+   */
+  pub fn set_time_scale(&mut self, time_scale: f32) {
+    self.time_scale = time_scale.max(0.0);
+  }
+
+  pub fn time_scale(&self) -> f32 {
+    self.time_scale
+  }
+
+  /**
+   * Приостанавливает симуляцию, пока мир на паузе
+   * update, step и advance не изменяют состояние мира
+   */
+  pub fn pause(&mut self) {
+    self.is_paused = true;
+  }
+
+  /**
+   * Возобновляет симуляцию, время, прошедшее на паузе,
+   * не учитывается в следующем update
+   */
+  pub fn resume(&mut self) {
+    self.is_paused = false;
+    self.last_update = None;
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.is_paused
+  }
+
+  /**
+   * Установка максимальной длительности шага симуляции (в секундах),
+   * 0 отключает разбиение шага на подшаги
+   */
+  pub fn set_max_step(&mut self, max_step: f32) {
+    self.max_step = seconds_to_micros(max_step).max(0);
+  }
+
+  /**
+   * Установка максимального количества подшагов за один шаг
+   */
+  pub fn set_max_substeps(&mut self, max_substeps: u32) {
+    self.max_substeps = max_substeps.max(1);
+  }
+
+  /**
+   * Проверка, что ограничительный прямоугольник тела с нижней
+   * центральной точкой (x, y) находится в границах мира
+   */
+  pub(crate) fn check_position(
+    &self, x: i32, y: i32, half_width: i32, height: i32
+  ) -> Result<(), PhysError> {
+    if x - half_width < 0 || x + half_width > self.width
+    || y - height < 0 || y > self.height {
+      return Err(PhysError::PositionOutOfWorld { x, y })
+    }
+
+    Ok(())
+  }
+
+  /**
+   * Проверка существования тела
+   */
+  pub(crate) fn check_body(&self, id: BodyId) -> Result<(), PhysError> {
+    if !self.rects.contains_key(&id) {
+      return Err(PhysError::UnknownBody(id))
+    }
+
+    Ok(())
+  }
+
+  /**
+   * Установка пользовательских данных только что созданного тела,
+   * используется функциями *_create_with_data
+   */
+  pub(crate) fn set_body_data(
+    &mut self, id: BodyId, user_data: u64, tags: u32
+  ) {
+    if let Some(rect) = self.rects.get_mut(&id) {
+      rect.data = BodyData { user_data, tags };
+    }
+  }
 
-    for event in &update_results.events {
-      result.push(event.class as i32);
-      result.push(event.body_id as i32);
-      result.push(event.trigger_id as i32);
+  /**
+   * Установка пользовательского значения тела
+   */
+  pub fn set_user_data(&mut self, id: BodyId, user_data: u64) {
+    if let Some(rect) = self.rects.get_mut(&id) {
+      rect.data.user_data = user_data;
     }
+  }
+
+  pub fn try_set_user_data(
+    &mut self, id: BodyId, user_data: u64
+  ) -> Result<(), PhysError> {
+    self.check_body(id)?;
+    self.set_user_data(id, user_data);
+    Ok(())
+  }
 
-    result.push(0);
-    result.push(0);
-    result.push(0);
+  /**
+   * Пользовательское значение тела, 0 для неизвестного тела
+   */
+  pub fn user_data(&self, id: BodyId) -> u64 {
+    match self.rects.get(&id) {
+      Some(rect) => rect.data.user_data,
+      None => 0
+    }
+  }
 
-    for position_update in &update_results.positions_updates {
-      result.push(position_update.id as i32);
-      result.push(position_update.x);
-      result.push(position_update.y);
+  /**
+   * Установка битового набора меток тела
+   */
+  pub fn set_tags(&mut self, id: BodyId, tags: u32) {
+    if let Some(rect) = self.rects.get_mut(&id) {
+      rect.data.tags = tags;
     }
+  }
 
-    Int32Array::from(&result[..])
+  pub fn try_set_tags(&mut self, id: BodyId, tags: u32) -> Result<(), PhysError> {
+    self.check_body(id)?;
+    self.set_tags(id, tags);
+    Ok(())
+  }
+
+  /**
+   * Битовый набор меток тела, 0 для неизвестного тела
+   */
+  pub fn tags(&self, id: BodyId) -> u32 {
+    match self.rects.get(&id) {
+      Some(rect) => rect.data.tags,
+      None => 0
+    }
   }
 
   pub fn remove(&mut self, id: BodyId) {
+    self.record(Input::Remove { id });
+
     self.ids_to_remove.insert(id);
   }
 
+  /**
+   * Удаляет тело, возвращает ошибку для неизвестного тела
+   */
+  pub fn try_remove(&mut self, id: BodyId) -> Result<(), PhysError> {
+    self.check_body(id)?;
+    self.remove(id);
+    Ok(())
+  }
+
   fn _get_possible_build_blocks(&self, player_id: BodyId) -> Vec<i32> {
     // 14 - максимальное число возможных ячеек для постройки блока
     // 14 * 2 = 28 координат
@@ -334,4 +826,50 @@ impl World {
   pub fn get_possible_build_blocks(&self, player_id: BodyId) -> Int32Array {
     Int32Array::from(&self._get_possible_build_blocks(player_id)[..])
   }
+}
+
+/**
+ * Количество элементов одного события в упакованных результатах
+ */
+#[cfg(target_arch = "wasm32")]
+const PACKED_EVENT_SIZE: usize = 10;
+
+/**
+ * Упаковка результатов обновления в плоский массив для передачи в JS:
+ * события по PACKED_EVENT_SIZE элементов (класс, тело, причина,
+ * владелец причины, user_data тела младшие и старшие 32 бита,
+ * tags тела, user_data причины младшие и старшие 32 бита,
+ * tags причины), разделитель из PACKED_EVENT_SIZE нулей,
+ * тройки изменений позиций (тело, x, y)
+ */
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn update_results_to_array(update_results: &UpdateResults) -> Int32Array {
+  let mut result = Vec::with_capacity(
+    update_results.events.len() * PACKED_EVENT_SIZE +
+    update_results.positions_updates.len() * 3 +
+    PACKED_EVENT_SIZE
+  );
+
+  for event in &update_results.events {
+    result.push(event.class as i32);
+    result.push(event.body_id as i32);
+    result.push(event.trigger_id as i32);
+    result.push(event.owner_id as i32);
+
+    for data in [&event.body_data, &event.trigger_data] {
+      result.push(data.user_data as u32 as i32);
+      result.push((data.user_data >> 32) as u32 as i32);
+      result.push(data.tags as i32);
+    }
+  }
+
+  result.resize(result.len() + PACKED_EVENT_SIZE, 0);
+
+  for position_update in &update_results.positions_updates {
+    result.push(position_update.id as i32);
+    result.push(position_update.x);
+    result.push(position_update.y);
+  }
+
+  Int32Array::from(&result[..])
 }
\ No newline at end of file